@@ -0,0 +1,12 @@
+DROP INDEX shelters_coordinates_idx;
+
+ALTER TABLE shelters
+    ADD COLUMN location JSONB;
+
+UPDATE shelters
+    SET location = jsonb_build_object('x', longitude, 'y', latitude);
+
+ALTER TABLE shelters
+    ALTER COLUMN location SET NOT NULL,
+    DROP COLUMN latitude,
+    DROP COLUMN longitude;
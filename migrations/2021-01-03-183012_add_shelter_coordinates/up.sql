@@ -0,0 +1,14 @@
+ALTER TABLE shelters
+    ADD COLUMN latitude  DOUBLE PRECISION,
+    ADD COLUMN longitude DOUBLE PRECISION;
+
+UPDATE shelters
+    SET latitude  = (location->>'y')::DOUBLE PRECISION,
+        longitude = (location->>'x')::DOUBLE PRECISION;
+
+ALTER TABLE shelters
+    ALTER COLUMN latitude SET NOT NULL,
+    ALTER COLUMN longitude SET NOT NULL,
+    DROP COLUMN location;
+
+CREATE INDEX shelters_coordinates_idx ON shelters (latitude, longitude);
@@ -0,0 +1,2 @@
+DROP INDEX shelter_measurements_signal_history_idx;
+DROP INDEX shelter_measurements_shelter_history_idx;
@@ -0,0 +1,4 @@
+CREATE INDEX shelter_measurements_shelter_history_idx
+    ON shelter_measurements (shelter_id, created_at, id);
+CREATE INDEX shelter_measurements_signal_history_idx
+    ON shelter_measurements (signal_id, created_at, id);
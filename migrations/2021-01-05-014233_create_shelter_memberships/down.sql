@@ -0,0 +1 @@
+DROP TABLE shelter_memberships;
@@ -0,0 +1,11 @@
+CREATE TABLE shelter_memberships (
+    id         UUID        PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    shelter_id UUID        NOT NULL REFERENCES shelters(id) ON DELETE CASCADE,
+    user_id    UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role       TEXT        NOT NULL,
+    UNIQUE (shelter_id, user_id)
+);
+
+CREATE INDEX shelter_memberships_user_idx ON shelter_memberships (user_id);
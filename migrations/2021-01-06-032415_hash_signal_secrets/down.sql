@@ -0,0 +1,6 @@
+-- Hashed secrets can't be recovered, so signals must be given new secrets
+-- after reverting this migration.
+ALTER TABLE signals
+    RENAME COLUMN secret_hash TO secret;
+ALTER TABLE signals
+    ADD CONSTRAINT signals_secret_key UNIQUE (secret);
@@ -0,0 +1,16 @@
+-- Replace each secret with a salted SHA-256 digest, in the format used by
+-- `service::SecretHash`.
+ALTER TABLE signals
+    DROP CONSTRAINT signals_secret_key,
+    ADD COLUMN secret_salt BYTEA;
+
+UPDATE signals SET secret_salt = gen_random_bytes(16);
+UPDATE signals SET secret =
+    'sha256:' ||
+    encode(secret_salt, 'base64') || ':' ||
+    encode(digest(secret_salt || convert_to(secret, 'UTF8'), 'sha256'), 'base64');
+
+ALTER TABLE signals
+    DROP COLUMN secret_salt;
+ALTER TABLE signals
+    RENAME COLUMN secret TO secret_hash;
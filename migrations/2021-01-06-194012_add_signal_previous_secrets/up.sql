@@ -0,0 +1,3 @@
+ALTER TABLE signals
+    ADD COLUMN previous_secret_hash       TEXT,
+    ADD COLUMN previous_secret_expires_at TIMESTAMPTZ;
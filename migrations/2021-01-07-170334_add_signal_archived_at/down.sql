@@ -0,0 +1,2 @@
+ALTER TABLE signals
+    DROP COLUMN archived_at;
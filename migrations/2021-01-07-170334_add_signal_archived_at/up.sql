@@ -0,0 +1,2 @@
+ALTER TABLE signals
+    ADD COLUMN archived_at TIMESTAMPTZ;
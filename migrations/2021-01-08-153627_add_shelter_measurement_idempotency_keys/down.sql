@@ -0,0 +1,4 @@
+DROP INDEX shelter_measurements_idempotency_key_idx;
+
+ALTER TABLE shelter_measurements
+    DROP COLUMN idempotency_key;
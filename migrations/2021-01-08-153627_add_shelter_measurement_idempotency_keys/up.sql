@@ -0,0 +1,5 @@
+ALTER TABLE shelter_measurements
+    ADD COLUMN idempotency_key TEXT;
+
+CREATE UNIQUE INDEX shelter_measurements_idempotency_key_idx
+    ON shelter_measurements (signal_id, idempotency_key);
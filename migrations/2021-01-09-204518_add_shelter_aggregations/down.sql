@@ -0,0 +1,6 @@
+ALTER TABLE shelter_measurements
+    DROP COLUMN value;
+
+ALTER TABLE shelters
+    DROP COLUMN primary_signal_id,
+    DROP COLUMN aggregation;
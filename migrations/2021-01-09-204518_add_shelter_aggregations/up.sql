@@ -0,0 +1,19 @@
+ALTER TABLE shelters
+    ADD COLUMN aggregation       TEXT NOT NULL DEFAULT 'latest',
+    ADD COLUMN primary_signal_id UUID REFERENCES signals(id) ON DELETE SET NULL;
+ALTER TABLE shelters
+    ALTER COLUMN aggregation DROP DEFAULT;
+
+-- Record each signal's own reading, which may differ from the shelter
+-- occupancy that results from it.
+ALTER TABLE shelter_measurements
+    ADD COLUMN value INT;
+UPDATE shelter_measurements AS measurements
+    SET value = CASE signals.measure
+        WHEN 'Spots' THEN measurements.occupied_spots
+        ELSE measurements.occupied_beds
+    END
+    FROM signals
+    WHERE signals.id = measurements.signal_id;
+ALTER TABLE shelter_measurements
+    ALTER COLUMN value SET NOT NULL;
@@ -0,0 +1,4 @@
+ALTER TABLE shelters
+    DROP COLUMN occupancy_updated_at;
+ALTER TABLE signals
+    DROP COLUMN last_seen_at;
@@ -0,0 +1,17 @@
+ALTER TABLE signals
+    ADD COLUMN last_seen_at TIMESTAMPTZ;
+UPDATE signals
+    SET last_seen_at = (
+        SELECT MAX(created_at)
+        FROM shelter_measurements
+        WHERE shelter_measurements.signal_id = signals.id
+    );
+
+ALTER TABLE shelters
+    ADD COLUMN occupancy_updated_at TIMESTAMPTZ;
+UPDATE shelters
+    SET occupancy_updated_at = (
+        SELECT MAX(created_at)
+        FROM shelter_measurements
+        WHERE shelter_measurements.shelter_id = shelters.id
+    );
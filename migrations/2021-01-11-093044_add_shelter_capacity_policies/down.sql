@@ -0,0 +1,4 @@
+ALTER TABLE shelter_measurements
+    DROP COLUMN status;
+ALTER TABLE shelters
+    DROP COLUMN capacity_policy;
@@ -0,0 +1,9 @@
+ALTER TABLE shelters
+    ADD COLUMN capacity_policy TEXT NOT NULL DEFAULT 'clamp';
+ALTER TABLE shelters
+    ALTER COLUMN capacity_policy DROP DEFAULT;
+
+ALTER TABLE shelter_measurements
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'accepted';
+ALTER TABLE shelter_measurements
+    ALTER COLUMN status DROP DEFAULT;
@@ -0,0 +1,18 @@
+CREATE TABLE measurement_anomalies (
+    id             UUID        PRIMARY KEY,
+    created_at     TIMESTAMPTZ NOT NULL,
+    updated_at     TIMESTAMPTZ NOT NULL,
+    measurement_id UUID        NOT NULL REFERENCES shelter_measurements(id) ON DELETE CASCADE,
+    shelter_id     UUID        NOT NULL REFERENCES shelters(id) ON DELETE CASCADE,
+    signal_id      UUID        NOT NULL REFERENCES signals(id) ON DELETE CASCADE,
+    kind           TEXT        NOT NULL,
+    score          FLOAT8      NOT NULL,
+    resolved_at    TIMESTAMPTZ,
+    resolved_by_id UUID        REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX measurement_anomalies_signal_idx
+    ON measurement_anomalies (signal_id, kind, created_at);
+CREATE INDEX measurement_anomalies_unresolved_idx
+    ON measurement_anomalies (created_at)
+    WHERE resolved_at IS NULL;
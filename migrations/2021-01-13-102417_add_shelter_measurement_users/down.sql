@@ -0,0 +1,8 @@
+DELETE FROM shelter_measurements
+    WHERE signal_id IS NULL;
+
+ALTER TABLE shelter_measurements
+    DROP CONSTRAINT shelter_measurements_source_check,
+    DROP COLUMN user_id,
+    ALTER COLUMN value SET NOT NULL,
+    ALTER COLUMN signal_id SET NOT NULL;
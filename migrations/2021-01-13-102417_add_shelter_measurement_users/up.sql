@@ -0,0 +1,12 @@
+ALTER TABLE shelter_measurements
+    ALTER COLUMN signal_id DROP NOT NULL,
+    ALTER COLUMN value DROP NOT NULL,
+    ADD COLUMN user_id UUID REFERENCES users (id);
+
+-- Each measurement is reported either by a signal, with its own reading, or
+-- by a user.
+ALTER TABLE shelter_measurements
+    ADD CONSTRAINT shelter_measurements_source_check CHECK (
+        num_nonnulls(signal_id, user_id) = 1
+        AND (signal_id IS NULL) = (value IS NULL)
+    );
@@ -0,0 +1,8 @@
+DELETE FROM shelter_measurements
+    WHERE correction_of_id IS NOT NULL;
+
+ALTER TABLE shelter_measurements
+    DROP COLUMN correction_of_id,
+    DROP COLUMN retraction_reason,
+    DROP COLUMN retracted_by_id,
+    DROP COLUMN retracted_at;
@@ -0,0 +1,5 @@
+ALTER TABLE shelter_measurements
+    ADD COLUMN retracted_at TIMESTAMPTZ,
+    ADD COLUMN retracted_by_id UUID REFERENCES users (id),
+    ADD COLUMN retraction_reason TEXT,
+    ADD COLUMN correction_of_id UUID REFERENCES shelter_measurements (id);
@@ -0,0 +1,2 @@
+ALTER TABLE signals
+    DROP COLUMN disabled_at;
@@ -0,0 +1,5 @@
+DROP INDEX shelters_earth_idx;
+CREATE INDEX shelters_coordinates_idx ON shelters (latitude, longitude);
+
+DROP EXTENSION earthdistance;
+DROP EXTENSION cube;
@@ -0,0 +1,6 @@
+CREATE EXTENSION IF NOT EXISTS cube;
+CREATE EXTENSION IF NOT EXISTS earthdistance;
+
+DROP INDEX shelters_coordinates_idx;
+CREATE INDEX shelters_earth_idx ON shelters
+    USING gist (ll_to_earth(latitude, longitude));
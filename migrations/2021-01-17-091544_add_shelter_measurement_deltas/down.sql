@@ -0,0 +1,2 @@
+ALTER TABLE shelter_measurements
+    DROP COLUMN delta;
@@ -0,0 +1,2 @@
+ALTER TABLE shelter_measurements
+    ADD COLUMN delta INTEGER;
@@ -8,10 +8,13 @@ use jwt::{decode_header, DecodingKey, Header, TokenData};
 use cache_control::CacheControl;
 use http::header::CACHE_CONTROL;
 use request::Client;
+use std::fs::read as read_file;
+use std::path::Path;
 use tokio::sync::Mutex;
 
 lazy_static! {
     static ref CLOCK_LEEWAY: ChronoDuration = ChronoDuration::seconds(30);
+    static ref JWKS_DEFAULT_MAX_AGE: ChronoDuration = ChronoDuration::hours(1);
 }
 
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
@@ -21,12 +24,26 @@ pub struct AuthClaims {
     pub aud: String,
     pub iss: String,
     pub sub: String,
-    pub name: String,
-    pub user_id: String,
-    pub email: String,
+
+    // Firebase-specific claims, which other providers may omit.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
     pub email_verified: bool,
 }
 
+impl AuthClaims {
+    /// The ID of the authenticated account, which is the Firebase user ID if
+    /// present, or else the token subject.
+    pub fn account_id(&self) -> &str {
+        self.user_id.as_deref().unwrap_or(&self.sub)
+    }
+}
+
 #[derive(Debug)]
 pub struct AuthInfo(TokenData<AuthClaims>);
 
@@ -55,17 +72,94 @@ pub trait Verifier: Sync + Send {
     async fn decode_token(&self, token: &str) -> Result<AuthInfo>;
 }
 
+#[async_trait]
+impl Verifier for Box<dyn Verifier> {
+    async fn decode_token(&self, token: &str) -> Result<AuthInfo> {
+        self.as_ref().decode_token(token).await
+    }
+}
+
+/// An `AuthProvider` determines how authentication tokens are verified.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthProvider {
+    /// Verify Firebase ID tokens.
+    Firebase,
+
+    /// Verify tokens signed with a shared HS256 secret.
+    Hs256,
+
+    /// Verify tokens signed with a static RS256 key.
+    Rs256,
+
+    /// Verify RS256 tokens against keys from a JWKS document.
+    Jwks,
+}
+
+impl Display for AuthProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let s = to_plain_string(self).map_err(|_| FmtError)?;
+        s.fmt(f)
+    }
+}
+
+impl FromStr for AuthProvider {
+    type Err = SerdePlainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_plain_str(s)
+    }
+}
+
+fn token_validation(
+    algorithm: JwtAlgorithm,
+    issuer: &str,
+    audience: &str,
+) -> TokenValidation {
+    let mut validation = TokenValidation::new(algorithm);
+    validation.leeway = CLOCK_LEEWAY.num_seconds().try_into().unwrap();
+    validation.iss = Some(issuer.to_owned());
+    validation.set_audience(&[audience]);
+    validation
+}
+
+fn decode_claims(
+    token: &str,
+    key: &DecodingKey,
+    validation: &TokenValidation,
+) -> Result<AuthInfo> {
+    let data = decode_token::<AuthClaims>(token, key, validation)?;
+
+    let iat = Utc.timestamp(
+        data.claims
+            .iat
+            .try_into()
+            .context("failed to convert issued-at time to u64")?,
+        0,
+    );
+    if iat > (Utc::now() + *CLOCK_LEEWAY) {
+        bail!("invalid issued-at time");
+    }
+
+    Ok(data.into())
+}
+
+fn decode_key_id(token: &str) -> Result<String> {
+    let header = decode_header(token).context("failed to decode header")?;
+    header.kid.context("missing key ID")
+}
+
 const FIREBASE_KEY_URL: &str = "https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com";
 const FIREBASE_ISS_URL: &str = "https://securetoken.google.com";
 
 pub struct FirebaseVerifier {
-    client: Mutex<FirebaseClient>,
+    client: Mutex<JwksClient>,
     project_id: String,
 }
 
 impl FirebaseVerifier {
     pub fn new(project_id: &str) -> Self {
-        let client = FirebaseClient::new(project_id.to_owned());
+        let client = JwksClient::new(FIREBASE_KEY_URL.to_owned());
         Self {
             client: Mutex::new(client),
             project_id: project_id.to_owned(),
@@ -86,14 +180,13 @@ impl FirebaseVerifier {
 #[async_trait]
 impl Verifier for FirebaseVerifier {
     async fn decode_token(&self, token: &str) -> Result<AuthInfo> {
-        let mut validation = TokenValidation::new(JwtAlgorithm::RS256);
-        validation.leeway = CLOCK_LEEWAY.num_seconds().try_into().unwrap();
-        validation.iss = Some(self.expected_iss());
-        validation.set_audience(&[self.expected_aud()]);
-
-        let header = decode_header(token).context("failed to decode header")?;
-        let kid = header.kid.context("missing key ID")?;
+        let validation = token_validation(
+            JwtAlgorithm::RS256,
+            &self.expected_iss(),
+            &self.expected_aud(),
+        );
 
+        let kid = decode_key_id(token)?;
         let keys = {
             let mut client = self.client.lock().await;
             client
@@ -102,36 +195,112 @@ impl Verifier for FirebaseVerifier {
                 .context("failed to load decoding keys")?
         };
         let key = keys.get(&kid).context("no matching decoding keys")?;
-        let data = decode_token::<AuthClaims>(token, key, &validation)?;
-
-        let iat = Utc.timestamp(
-            data.claims
-                .iat
-                .try_into()
-                .context("failed to convert issued-at time to u64")?,
-            0,
-        );
-        if iat > (Utc::now() + *CLOCK_LEEWAY) {
-            bail!("invalid issued-at time");
-        }
+        decode_claims(token, key, &validation)
+    }
+}
+
+/// A `StaticKeyVerifier` verifies tokens signed with a single, preconfigured
+/// key.
+pub struct StaticKeyVerifier {
+    key: DecodingKey<'static>,
+    validation: TokenValidation,
+}
+
+impl StaticKeyVerifier {
+    pub fn hs256(secret: &[u8], issuer: &str, audience: &str) -> Self {
+        let key = DecodingKey::from_secret(secret).into_static();
+        let validation =
+            token_validation(JwtAlgorithm::HS256, issuer, audience);
+        Self { key, validation }
+    }
+
+    pub fn rs256(pem: &[u8], issuer: &str, audience: &str) -> Result<Self> {
+        let key = DecodingKey::from_rsa_pem(pem)
+            .context("invalid RSA public key")?
+            .into_static();
+        let validation =
+            token_validation(JwtAlgorithm::RS256, issuer, audience);
+        Ok(Self { key, validation })
+    }
+}
+
+#[async_trait]
+impl Verifier for StaticKeyVerifier {
+    async fn decode_token(&self, token: &str) -> Result<AuthInfo> {
+        decode_claims(token, &self.key, &self.validation)
+    }
+}
+
+/// A `JwksVerifier` verifies RS256 tokens against the keys in a JWKS
+/// document, which is either read from a file once or fetched from a URL
+/// (and refreshed as it expires).
+pub struct JwksVerifier {
+    keys: JwksKeys,
+    validation: TokenValidation,
+}
+
+enum JwksKeys {
+    Static(Map<String, DecodingKey<'static>>),
+    Remote(Mutex<JwksClient>),
+}
+
+impl JwksVerifier {
+    pub fn from_file(
+        path: &Path,
+        issuer: &str,
+        audience: &str,
+    ) -> Result<Self> {
+        let data = read_file(path).context("failed to read JWKS file")?;
+        let data: JwkData =
+            json::from_slice(&data).context("failed to parse JWKS file")?;
+        let keys = JwksKeys::Static(data.decoding_keys());
+        let validation =
+            token_validation(JwtAlgorithm::RS256, issuer, audience);
+        Ok(Self { keys, validation })
+    }
 
-        Ok(data.into())
+    pub fn from_url(url: &str, issuer: &str, audience: &str) -> Self {
+        let client = JwksClient::new(url.to_owned());
+        let keys = JwksKeys::Remote(Mutex::new(client));
+        let validation =
+            token_validation(JwtAlgorithm::RS256, issuer, audience);
+        Self { keys, validation }
+    }
+}
+
+#[async_trait]
+impl Verifier for JwksVerifier {
+    async fn decode_token(&self, token: &str) -> Result<AuthInfo> {
+        let kid = decode_key_id(token)?;
+        let key = match &self.keys {
+            JwksKeys::Static(keys) => keys.get(&kid).cloned(),
+            JwksKeys::Remote(client) => {
+                let mut client = client.lock().await;
+                let keys = client
+                    .keys()
+                    .await
+                    .context("failed to load decoding keys")?;
+                keys.get(&kid).cloned()
+            }
+        };
+        let key = key.context("no matching decoding keys")?;
+        decode_claims(token, &key, &self.validation)
     }
 }
 
 #[derive(Debug, Clone)]
-struct FirebaseClient {
+struct JwksClient {
     client: Client,
-    project_id: String,
+    url: String,
     refresh_at: DateTime,
     keys: Map<String, DecodingKey<'static>>,
 }
 
-impl FirebaseClient {
-    pub fn new(project_id: String) -> Self {
+impl JwksClient {
+    pub fn new(url: String) -> Self {
         Self {
             client: Client::new(),
-            project_id,
+            url,
             refresh_at: Utc::now(),
             keys: Default::default(),
         }
@@ -143,38 +312,32 @@ impl FirebaseClient {
     }
 }
 
-impl FirebaseClient {
+impl JwksClient {
     async fn sync(&mut self) -> Result<()> {
         let refresh_at = self.refresh_at - ChronoDuration::minutes(1);
         if Utc::now() <= refresh_at {
             return Ok(());
         }
 
-        let response = self.client.get(FIREBASE_KEY_URL).send().await?;
+        let response = self.client.get(&self.url).send().await?;
 
-        let cache_control = response
-            .headers()
-            .get(CACHE_CONTROL)
-            .context("missing cache-control header")?;
-        let cache_control = cache_control
-            .to_str()
-            .context("failed to parse cache-control header")?;
-        let cache_control = CacheControl::from_value(cache_control)
-            .context("missing cache-control directives")?;
-
-        let max_age = cache_control.max_age.context("missing max-age")?;
+        // Refresh keys as directed by the cache-control header, if any.
+        let max_age = match response.headers().get(CACHE_CONTROL) {
+            Some(cache_control) => {
+                let cache_control = cache_control
+                    .to_str()
+                    .context("failed to parse cache-control header")?;
+                let cache_control = CacheControl::from_value(cache_control)
+                    .context("missing cache-control directives")?;
+                cache_control.max_age.context("missing max-age")?
+            }
+            None => *JWKS_DEFAULT_MAX_AGE,
+        };
         self.refresh_at = Utc::now() + max_age;
 
         let data: JwkData =
             response.json().await.context("failed to parse response")?;
-        let mut keys = Map::<String, DecodingKey<'static>>::new();
-        for jwk in data.keys {
-            let JwkInfo { kid, n, e } = jwk;
-            let key = DecodingKey::from_rsa_components(&n, &e).into_static();
-            keys.insert(kid, key);
-        }
-
-        self.keys = keys;
+        self.keys = data.decoding_keys();
         Ok(())
     }
 }
@@ -184,6 +347,18 @@ struct JwkData {
     keys: Vec<JwkInfo>,
 }
 
+impl JwkData {
+    fn decoding_keys(self) -> Map<String, DecodingKey<'static>> {
+        let mut keys = Map::<String, DecodingKey<'static>>::new();
+        for jwk in self.keys {
+            let JwkInfo { kid, n, e } = jwk;
+            let key = DecodingKey::from_rsa_components(&n, &e).into_static();
+            keys.insert(kid, key);
+        }
+        keys
+    }
+}
+
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 struct JwkInfo {
     kid: String,
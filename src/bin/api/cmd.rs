@@ -8,6 +8,6 @@ pub use serve::*;
 
 #[derive(Debug, Clap)]
 pub enum Command {
-    Serve(ServeCli),
+    Serve(Box<ServeCli>),
     Migrate(MigrateCli),
 }
@@ -4,12 +4,17 @@ use api::routes::graphql::graphql as graphql_route;
 use api::routes::graphql::playground as playground_route;
 use api::routes::healthz::healthz as healthz_route;
 use api::routes::recover;
+use api::routes::signals::signal_heartbeats as signal_heartbeats_route;
+use api::routes::signals::signal_measurement_batches as signal_measurement_batches_route;
+use api::routes::signals::signal_measurements as signal_measurements_route;
 
 use api::graphql::extensions::Logging as LoggingExtension;
-use api::graphql::{Mutation, Query};
+use api::graphql::{Mutation, Query, Subscription};
 
-use api::auth::FirebaseVerifier;
+use api::auth::{AuthProvider, Verifier};
+use api::auth::{FirebaseVerifier, JwksVerifier, StaticKeyVerifier};
 use api::service::Service;
+use api::service::{RateLimit, RateLimiter};
 
 use warp::any as warp_any;
 use warp::cors;
@@ -23,11 +28,15 @@ use http::Method;
 use tokio::runtime::Runtime;
 use tokio_compat::FutureExt;
 
+use std::fs::read as read_file;
 use std::net::ToSocketAddrs;
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use chrono::Duration as ChronoDuration;
+
 use graphql::extensions::ApolloTracing as TracingExtension;
-use graphql::{EmptySubscription, Schema};
+use graphql::Schema;
 
 macro_rules! info {
     ($($arg:tt)+) => (
@@ -76,6 +85,15 @@ pub struct ServeCli {
     #[clap(help_heading = Some("SERVER"))]
     pub cors_origin: Vec<String>,
 
+    #[clap(
+        long,
+        env = "API_TRUST_FORWARDED_FOR",
+        about = "Identify clients by the X-Forwarded-For header of a proxy",
+        takes_value = false
+    )]
+    #[clap(help_heading = Some("SERVER"))]
+    pub trust_forwarded_for: bool,
+
     #[clap(
         long,
         env = "API_DATABASE_URL",
@@ -95,6 +113,107 @@ pub struct ServeCli {
     #[clap(help_heading = Some("DATABASE"))]
     pub database_max_connections: Option<u32>,
 
+    #[clap(
+        long,
+        env = "API_SIGNAL_STALE_AFTER",
+        about = "Seconds after which a silent signal is considered stale",
+        value_name = "SECONDS"
+    )]
+    #[clap(help_heading = Some("SIGNALS"))]
+    pub signal_stale_after: Option<u32>,
+
+    #[clap(
+        long,
+        env = "API_SIGNAL_RATE_LIMIT",
+        about = "Measurements each signal can report per minute",
+        value_name = "N"
+    )]
+    #[clap(help_heading = Some("SIGNALS"))]
+    pub signal_rate_limit: Option<u32>,
+
+    #[clap(
+        long,
+        env = "API_ADDRESS_RATE_LIMIT",
+        about = "Measurements that can be reported per minute from an address",
+        value_name = "N"
+    )]
+    #[clap(help_heading = Some("SIGNALS"))]
+    pub address_rate_limit: Option<u32>,
+
+    #[clap(
+        long,
+        env = "API_SIGNAL_MAX_VIOLATIONS",
+        about = "Rate limit violations per hour after which a signal is disabled",
+        value_name = "N"
+    )]
+    #[clap(help_heading = Some("SIGNALS"))]
+    pub signal_max_violations: Option<u32>,
+
+    #[clap(
+        long,
+        env = "API_AUTH_PROVIDER",
+        about = "How to verify authentication tokens",
+        value_name = "PROVIDER",
+        default_value = "firebase"
+    )]
+    #[clap(help_heading = Some("AUTH"))]
+    pub auth_provider: AuthProvider,
+
+    #[clap(
+        long,
+        env = "API_AUTH_SECRET",
+        about = "Shared secret for HS256 tokens",
+        value_name = "SECRET",
+        hide_env_values = true
+    )]
+    #[clap(help_heading = Some("AUTH"))]
+    pub auth_secret: Option<String>,
+
+    #[clap(
+        long,
+        env = "API_AUTH_PUBLIC_KEY",
+        about = "Path to a PEM-encoded public key for RS256 tokens",
+        value_name = "PATH"
+    )]
+    #[clap(help_heading = Some("AUTH"))]
+    pub auth_public_key: Option<PathBuf>,
+
+    #[clap(
+        long,
+        env = "API_AUTH_JWKS_FILE",
+        about = "Path to a JWKS document with keys for RS256 tokens",
+        value_name = "PATH"
+    )]
+    #[clap(help_heading = Some("AUTH"))]
+    pub auth_jwks_file: Option<PathBuf>,
+
+    #[clap(
+        long,
+        env = "API_AUTH_JWKS_URL",
+        about = "URL of a JWKS document with keys for RS256 tokens",
+        value_name = "URL"
+    )]
+    #[clap(help_heading = Some("AUTH"))]
+    pub auth_jwks_url: Option<String>,
+
+    #[clap(
+        long,
+        env = "API_AUTH_ISSUER",
+        about = "Issuer of non-Firebase tokens (required unless using Firebase)",
+        value_name = "ISSUER"
+    )]
+    #[clap(help_heading = Some("AUTH"))]
+    pub auth_issuer: Option<String>,
+
+    #[clap(
+        long,
+        env = "API_AUTH_AUDIENCE",
+        about = "Audience of non-Firebase tokens (required unless using Firebase)",
+        value_name = "AUDIENCE"
+    )]
+    #[clap(help_heading = Some("AUTH"))]
+    pub auth_audience: Option<String>,
+
     #[clap(
         long,
         env = "API_FIREBASE_PROJECT_ID",
@@ -103,7 +222,7 @@ pub struct ServeCli {
         hide_env_values = true
     )]
     #[clap(help_heading = Some("FIREBASE"))]
-    pub firebase_project_id: String,
+    pub firebase_project_id: Option<String>,
 }
 
 pub fn serve(ctx: Context, cli: ServeCli) -> Result<()> {
@@ -118,16 +237,32 @@ pub fn serve(ctx: Context, cli: ServeCli) -> Result<()> {
             .context("failed to connect to database")?
     };
 
-    let service = Service::builder()
-        .db_pool(db_pool)
-        .build()
-        .context("failed to initialize service")?;
+    let service = {
+        let mut builder = Service::builder();
+        builder.db_pool(db_pool);
+        if let Some(seconds) = cli.signal_stale_after {
+            builder.signal_stale_after(ChronoDuration::seconds(seconds.into()));
+        }
+        if let Some(limit) = cli.signal_rate_limit {
+            let limit = RateLimit::per_minute(limit);
+            builder.signal_rate_limiter(RateLimiter::new(limit));
+        }
+        if let Some(limit) = cli.address_rate_limit {
+            let limit = RateLimit::per_minute(limit);
+            builder.address_rate_limiter(RateLimiter::new(limit));
+        }
+        if let Some(limit) = cli.signal_max_violations {
+            let limit = RateLimit::per_hour(limit);
+            builder.signal_violation_limiter(RateLimiter::new(limit));
+        }
+        builder.build().context("failed to initialize service")?
+    };
     let service = Arc::new(service);
 
     let schema = {
         let query = Query::new();
         let mutation = Mutation::new();
-        let subscription = EmptySubscription;
+        let subscription = Subscription::new();
 
         let mut schema = Schema::build(query, mutation, subscription)
             .extension(LoggingExtension)
@@ -143,19 +278,44 @@ pub fn serve(ctx: Context, cli: ServeCli) -> Result<()> {
     let runtime = Runtime::new().context("failed to initialize runtime")?;
     let runtime = Arc::new(runtime);
 
-    let firebase_project_id = &cli.firebase_project_id;
-    let verifier = FirebaseVerifier::new(firebase_project_id);
+    let verifier =
+        build_verifier(&cli).context("failed to initialize verifier")?;
     let verifier = Arc::new(verifier);
 
     let playground = warp_any().and(playground_route());
     let graphql = warp_path("graphql").and(graphql_route(
         schema,
         runtime.clone(),
-        service,
+        service.clone(),
         verifier,
+        cli.trust_forwarded_for,
     ));
     let healthz = warp_path("healthz").and(healthz_route());
-    let routes = warp_root().and(playground).or(healthz).or(graphql);
+    let signals = {
+        let trust_forwarded_for = cli.trust_forwarded_for;
+        warp_path("signals").and(
+            signal_measurements_route(
+                runtime.clone(),
+                service.clone(),
+                trust_forwarded_for,
+            )
+            .or(signal_measurement_batches_route(
+                runtime.clone(),
+                service.clone(),
+                trust_forwarded_for,
+            ))
+            .or(signal_heartbeats_route(
+                runtime.clone(),
+                service,
+                trust_forwarded_for,
+            )),
+        )
+    };
+    let routes = warp_root()
+        .and(playground)
+        .or(healthz)
+        .or(signals)
+        .or(graphql);
 
     let cors = cors()
         .allow_credentials(true)
@@ -183,6 +343,64 @@ pub fn serve(ctx: Context, cli: ServeCli) -> Result<()> {
     Ok(())
 }
 
+fn build_verifier(cli: &ServeCli) -> Result<Box<dyn Verifier>> {
+    let ServeCli {
+        auth_provider: provider,
+        auth_issuer: issuer,
+        auth_audience: audience,
+        ..
+    } = cli;
+    let issuer = || issuer.as_deref().context("missing issuer");
+    let audience = || audience.as_deref().context("missing audience");
+
+    info!("using {} auth provider", provider);
+    let verifier: Box<dyn Verifier> = match provider {
+        AuthProvider::Firebase => {
+            let project_id = cli
+                .firebase_project_id
+                .as_ref()
+                .context("missing Firebase project ID")?;
+            let verifier = FirebaseVerifier::new(project_id);
+            Box::new(verifier)
+        }
+        AuthProvider::Hs256 => {
+            let secret = cli.auth_secret.as_ref().context("missing secret")?;
+            let verifier = StaticKeyVerifier::hs256(
+                secret.as_bytes(),
+                issuer()?,
+                audience()?,
+            );
+            Box::new(verifier)
+        }
+        AuthProvider::Rs256 => {
+            let path =
+                cli.auth_public_key.as_ref().context("missing public key")?;
+            let pem = read_file(path).context("failed to read public key")?;
+            let verifier =
+                StaticKeyVerifier::rs256(&pem, issuer()?, audience()?)?;
+            Box::new(verifier)
+        }
+        AuthProvider::Jwks => {
+            let (issuer, audience) = (issuer()?, audience()?);
+            let verifier = match (&cli.auth_jwks_file, &cli.auth_jwks_url) {
+                (Some(path), None) => {
+                    JwksVerifier::from_file(path, issuer, audience)?
+                }
+                (None, Some(url)) => {
+                    JwksVerifier::from_url(url, issuer, audience)
+                }
+                _ => {
+                    let message =
+                        "exactly one of a JWKS file or URL is required";
+                    return Err(anyhow!(message));
+                }
+            };
+            Box::new(verifier)
+        }
+    };
+    Ok(verifier)
+}
+
 fn connect_db_pool(url: &str, max_connections: Option<u32>) -> Result<PgPool> {
     let manager = {
         let manager = DbConnectionManager::new(url);
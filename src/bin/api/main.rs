@@ -98,7 +98,7 @@ fn main() -> Result<()> {
     // Run command.
     use Command::*;
     match cli.cmd {
-        Serve(cli) => serve(ctx, cli),
+        Serve(cli) => serve(ctx, *cli),
         Migrate(cli) => migrate(ctx, cli),
     }
 }
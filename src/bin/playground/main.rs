@@ -53,7 +53,7 @@ pub fn main() -> Result<()> {
 
     let runtime = Runtime::new().context("failed to initialize runtime")?;
     runtime.block_on(async move {
-        let context = Context::default();
+        let context = Context::system();
         let request = {
             let firebase_id = "fake-firebase-id";
             let first_name = "Steven";
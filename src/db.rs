@@ -5,3 +5,5 @@ pub use diesel::Connection as DbConnection;
 pub use diesel::PgConnection;
 
 pub type PgPool = DbPool<DbConnectionManager<PgConnection>>;
+
+pub mod functions;
@@ -0,0 +1,47 @@
+use diesel::sql_types::{Double, Int4, Jsonb, Nullable, Text, Timestamptz};
+
+sql_function! {
+    /// Returns the first of its arguments that is not null.
+    fn coalesce(value: Nullable<Int4>, fallback: Int4) -> Int4;
+}
+
+sql_function! {
+    /// Extracts the text value at `path` within a JSONB object.
+    fn jsonb_extract_path_text(from_json: Jsonb, path: Text) -> Text;
+}
+
+sql_function! {
+    /// Converts a string to lower case.
+    fn lower(value: Text) -> Text;
+}
+
+sql_function! {
+    /// Extracts a field, such as the day of the week or hour, from a
+    /// timestamp.
+    fn date_part(field: Text, source: Timestamptz) -> Double;
+}
+
+/// A point on the surface of the earth, as represented by the
+/// `earthdistance` extension.
+#[derive(SqlType)]
+#[postgres(type_name = "earth")]
+pub struct Earth;
+
+/// A box in three-dimensional space, as represented by the `cube` extension.
+#[derive(SqlType)]
+#[postgres(type_name = "cube")]
+pub struct Cube;
+
+sql_function! {
+    /// Converts a latitude and longitude (in degrees) to a point on the
+    /// surface of the earth.
+    fn ll_to_earth(latitude: Double, longitude: Double) -> Earth;
+}
+
+sql_function! {
+    /// Returns a box that contains every point within `radius` meters of
+    /// `center`, and can be matched against a GiST index with `@>`.
+    fn earth_box(center: Earth, radius: Double) -> Cube;
+}
+
+diesel_infix_operator!(CubeContains, " @> ", backend: diesel::pg::Pg);
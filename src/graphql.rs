@@ -12,15 +12,17 @@ mod prelude {
 
     pub use graphql::Context;
     pub use graphql::Error as FieldError;
+    pub use graphql::ErrorExtensions;
+    pub use graphql::Interface;
     pub use graphql::Result as FieldResult;
     pub use graphql::{Enum, EnumType};
     pub use graphql::{InputObject, MergedObject, Object, SimpleObject};
     pub use graphql::{InputValueError, InputValueResult, Number, Value};
+    pub use graphql::{MergedSubscription, Subscription};
     pub use graphql::{Scalar, ScalarType};
 
-    // use graphql::connection::{Connection as GraphQLConnection, EmptyFields};
-    // pub type Connection<T, E = EmptyFields> =
-    //     GraphQLConnection<i64, T, EmptyFields, E>;
+    // Shadow `diesel::prelude::Connection`.
+    pub use super::connection::Connection;
 
     pub use diesel::delete as delete_from;
     pub use diesel::insert_into;
@@ -30,7 +32,14 @@ mod prelude {
 
     pub fn format_error(error: Error) -> FieldError {
         let message = format!("{:#}", error);
-        FieldError::new(message)
+        let field_error = FieldError::new(message);
+
+        // Give errors that clients are expected to handle a code.
+        if error.downcast_ref::<service::RateLimitedError>().is_some() {
+            return field_error
+                .extend_with(|_, e| e.set("code", "RATE_LIMITED"));
+        }
+        field_error
     }
 
     pub trait FieldResultExtension<T> {
@@ -53,6 +62,9 @@ pub mod extensions;
 pub mod address;
 pub use address::*;
 
+pub mod connection;
+pub use connection::*;
+
 pub mod context;
 pub use context::*;
 
@@ -65,12 +77,21 @@ pub use self::geo::*;
 pub mod id;
 pub use id::*;
 
+pub mod loader;
+pub use loader::*;
+
+pub mod measurement_anomaly;
+pub use measurement_anomaly::*;
+
 pub mod meta;
 pub use self::meta::*;
 
 pub mod mutation;
 pub use mutation::*;
 
+pub mod node;
+pub use node::*;
+
 pub mod query;
 pub use query::*;
 
@@ -80,8 +101,14 @@ pub use shelter::*;
 pub mod shelter_measurement;
 pub use shelter_measurement::*;
 
+pub mod shelter_membership;
+pub use shelter_membership::*;
+
 pub mod signal;
 pub use signal::*;
 
+pub mod subscription;
+pub use subscription::*;
+
 pub mod user;
 pub use user::*;
@@ -0,0 +1,197 @@
+use super::prelude::*;
+
+use graphql::connection::Connection as GraphQLConnection;
+use graphql::connection::{CursorType, Edge, EmptyFields};
+
+/// The number of edges fetched when neither `first` nor `last` is given.
+const DEFAULT_PAGE_SIZE: u32 = 25;
+
+/// The largest number of edges that can be fetched at once.
+const MAX_PAGE_SIZE: u32 = 100;
+
+pub type Connection<T> =
+    GraphQLConnection<Cursor, T, ConnectionFields, EmptyFields>;
+
+#[derive(Debug, Clone, Hash, SimpleObject)]
+pub struct ConnectionFields {
+    /// The total number of items in the connection.
+    pub total_count: u32,
+}
+
+impl CursorType for Cursor {
+    type Error = Error;
+
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+
+    fn encode_cursor(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// A `ConnectionWindow` is the range of offsets selected by a set of
+/// connection arguments.
+#[derive(Debug, Clone, Copy, Hash)]
+pub struct ConnectionWindow {
+    start: i64,
+    end: i64,
+    total: i64,
+}
+
+impl ConnectionWindow {
+    pub fn new(
+        after: Option<Cursor>,
+        before: Option<Cursor>,
+        first: Option<u32>,
+        last: Option<u32>,
+        total: u32,
+    ) -> Result<Self> {
+        if first.is_some() && last.is_some() {
+            bail!("`first` and `last` cannot be used together");
+        }
+        assert_page_size(first, last)?;
+
+        let total = i64::from(total);
+        let mut start = match after {
+            Some(after) => after.as_offset().context("invalid cursor")? + 1,
+            None => 0,
+        };
+        let mut end = match before {
+            Some(before) => before.as_offset().context("invalid cursor")?,
+            None => total,
+        };
+        start = start.min(total);
+        end = end.min(total).max(start);
+
+        match (first, last) {
+            (Some(first), _) => end = end.min(start + i64::from(first)),
+            (_, Some(last)) => start = start.max(end - i64::from(last)),
+            (None, None) => end = end.min(start + i64::from(DEFAULT_PAGE_SIZE)),
+        }
+
+        let window = ConnectionWindow { start, end, total };
+        Ok(window)
+    }
+
+    pub fn offset(&self) -> u32 {
+        self.start.try_into().expect("offset should fit in u32")
+    }
+
+    pub fn limit(&self) -> u32 {
+        let limit = self.end - self.start;
+        limit.try_into().expect("limit should fit in u32")
+    }
+
+    /// Build a `Connection` from the nodes within this window.
+    pub fn connect<T, N>(&self, nodes: Vec<N>) -> Connection<T>
+    where
+        N: Into<T>,
+    {
+        let Self { start, end, total } = *self;
+        let fields = ConnectionFields {
+            total_count: total.try_into().expect("total should fit in u32"),
+        };
+
+        let mut connection =
+            Connection::with_additional_fields(start > 0, end < total, fields);
+        let edges = nodes.into_iter().enumerate().map(|(index, node)| {
+            let cursor = Cursor::new(start + index as i64);
+            Edge::new(cursor, node.into())
+        });
+        connection.append(edges);
+        connection
+    }
+}
+
+/// Assert that no more than `MAX_PAGE_SIZE` edges are requested.
+fn assert_page_size(first: Option<u32>, last: Option<u32>) -> Result<()> {
+    if first.or(last).unwrap_or_default() > MAX_PAGE_SIZE {
+        bail!("`first` and `last` cannot exceed {}", MAX_PAGE_SIZE);
+    }
+    Ok(())
+}
+
+/// A `KeysetWindow` selects the edges of a connection relative to the
+/// `(timestamp, ID)` keys of its cursors.
+#[derive(Debug, Clone, Copy, Hash)]
+pub struct KeysetWindow {
+    pub after: Option<(DateTime, Uuid)>,
+    pub before: Option<(DateTime, Uuid)>,
+    pub limit: u32,
+
+    /// Whether edges are selected from the end of the connection.
+    pub from_end: bool,
+}
+
+impl KeysetWindow {
+    pub fn new(
+        after: Option<Cursor>,
+        before: Option<Cursor>,
+        first: Option<u32>,
+        last: Option<u32>,
+    ) -> Result<Self> {
+        let (limit, from_end) = match (first, last) {
+            (Some(_), Some(_)) => {
+                bail!("`first` and `last` cannot be used together")
+            }
+            (Some(first), None) => (first, false),
+            (None, Some(last)) => (last, true),
+            (None, None) => (DEFAULT_PAGE_SIZE, false),
+        };
+        assert_page_size(first, last)?;
+
+        let after = after
+            .map(|after| after.as_key())
+            .transpose()
+            .context("invalid cursor")?;
+        let before = before
+            .map(|before| before.as_key())
+            .transpose()
+            .context("invalid cursor")?;
+
+        let window = KeysetWindow {
+            after,
+            before,
+            limit,
+            from_end,
+        };
+        Ok(window)
+    }
+
+    /// Build a `Connection` from the nodes within this window.
+    ///
+    /// `has_more` indicates whether there are nodes beyond the window, in
+    /// the direction that they were selected.
+    pub fn connect<T, N, K>(
+        &self,
+        nodes: Vec<N>,
+        has_more: bool,
+        total_count: u32,
+        key: K,
+    ) -> Connection<T>
+    where
+        N: Into<T>,
+        K: Fn(&N) -> (DateTime, Uuid),
+    {
+        let (has_previous_page, has_next_page) = if self.from_end {
+            (has_more, self.before.is_some())
+        } else {
+            (self.after.is_some(), has_more)
+        };
+        let fields = ConnectionFields { total_count };
+
+        let mut connection = Connection::with_additional_fields(
+            has_previous_page,
+            has_next_page,
+            fields,
+        );
+        let edges = nodes.into_iter().map(|node| {
+            let (timestamp, id) = key(&node);
+            let cursor = Cursor::from_key(timestamp, id);
+            Edge::new(cursor, node.into())
+        });
+        connection.append(edges);
+        connection
+    }
+}
@@ -8,6 +8,10 @@ pub fn get_service<'a>(
     (service.as_ref(), context)
 }
 
+pub fn get_loaders<'a>(ctx: &'a Context<'_>) -> &'a Loaders {
+    ctx.data_unchecked::<Loaders>()
+}
+
 pub fn get_auth<'a>(ctx: &'a Context<'_>) -> Option<&'a AuthInfo> {
     ctx.data_opt()
 }
@@ -4,16 +4,40 @@ use base64::decode_config as decode_base64;
 use base64::encode_config as encode_base64;
 use base64::URL_SAFE_NO_PAD;
 
+use chrono::SecondsFormat;
+
+/// A `Cursor` is an opaque position within a connection.
+///
+/// Offset-paginated connections encode an offset, and keyset-paginated
+/// connections encode a `(timestamp, ID)` key.
 #[derive(Debug, Clone, Hash)]
-pub struct Cursor(u32);
+pub struct Cursor(String);
 
 impl Cursor {
     pub fn new(offset: i64) -> Cursor {
-        Cursor(offset.try_into().expect("offset should be non-negative"))
+        let offset: u32 =
+            offset.try_into().expect("offset should be non-negative");
+        Cursor(offset.to_string())
+    }
+
+    pub fn as_offset(&self) -> Result<i64> {
+        let offset: u32 = self.0.parse().context("not an offset cursor")?;
+        Ok(offset.into())
+    }
+
+    pub fn from_key(timestamp: DateTime, id: Uuid) -> Cursor {
+        let timestamp = timestamp.to_rfc3339_opts(SecondsFormat::Micros, true);
+        Cursor(format!("{}/{}", timestamp, id))
     }
 
-    pub fn as_offset(&self) -> i64 {
-        self.0.into()
+    pub fn as_key(&self) -> Result<(DateTime, Uuid)> {
+        let mut parts = self.0.splitn(2, '/');
+        let timestamp = parts.next().context("missing timestamp")?;
+        let id = parts.next().context("not a key cursor")?;
+        let timestamp = chrono::DateTime::parse_from_rfc3339(timestamp)
+            .context("failed to parse timestamp")?;
+        let id = id.parse().context("failed to parse ID")?;
+        Ok((timestamp.into(), id))
     }
 }
 
@@ -21,18 +45,16 @@ impl FromStr for Cursor {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let offset = decode_base64(s, URL_SAFE_NO_PAD)
+        let data = decode_base64(s, URL_SAFE_NO_PAD)
             .context("failed to decode base64")?;
-        let offset = String::from_utf8(offset).context("invald UTF-8")?;
-        let offset: u32 = offset.parse().context("failed to parse offset")?;
-        Ok(Self(offset))
+        let data = String::from_utf8(data).context("invald UTF-8")?;
+        Ok(Self(data))
     }
 }
 
 impl Display for Cursor {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        let offset = self.0.to_string();
-        let data = encode_base64(offset, URL_SAFE_NO_PAD);
+        let data = encode_base64(&self.0, URL_SAFE_NO_PAD);
         data.fmt(f)
     }
 }
@@ -54,3 +76,49 @@ impl ScalarType for Cursor {
         Value::String(self.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_cursor_round_trips() {
+        let cursor = Cursor::new(42);
+        let cursor: Cursor = cursor.to_string().parse().unwrap();
+        assert_eq!(cursor.as_offset().unwrap(), 42);
+        assert!(cursor.as_key().is_err());
+    }
+
+    #[test]
+    fn key_cursor_round_trips() {
+        let timestamp = Utc.ymd(2021, 1, 7).and_hms_micro(12, 30, 15, 123_456);
+        let id = Uuid::new_v4();
+        let cursor = Cursor::from_key(timestamp, id);
+        let cursor: Cursor = cursor.to_string().parse().unwrap();
+        assert_eq!(cursor.as_key().unwrap(), (timestamp, id));
+        assert!(cursor.as_offset().is_err());
+    }
+
+    #[test]
+    fn key_cursor_is_url_safe() {
+        let timestamp = Utc.ymd(2021, 1, 7).and_hms(12, 0, 0);
+        let cursor = Cursor::from_key(timestamp, Uuid::new_v4()).to_string();
+        assert!(cursor
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn malformed_cursors_are_rejected() {
+        assert!("not base64!".parse::<Cursor>().is_err());
+
+        let cursor = Cursor("2021-01-07T12:00:00Z/not-a-uuid".to_owned());
+        assert!(cursor.as_key().is_err());
+
+        let cursor = Cursor(format!("yesterday/{}", Uuid::new_v4()));
+        assert!(cursor.as_key().is_err());
+
+        let cursor = Cursor("-1".to_owned());
+        assert!(cursor.as_offset().is_err());
+    }
+}
@@ -20,6 +20,10 @@ impl Id {
         Id { uuid, type_name }
     }
 
+    pub fn is<T: Type>(&self) -> bool {
+        T::type_name() == self.type_name
+    }
+
     pub fn get<T: Type>(&self) -> Result<Uuid> {
         let expected = &T::type_name();
         let received = &self.type_name;
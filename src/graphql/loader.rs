@@ -0,0 +1,235 @@
+use super::prelude::*;
+
+use graphql::dataloader::{DataLoader, Loader};
+use std::iter::once;
+
+use futures_util::future::{BoxFuture, Shared};
+
+use service::GetLatestShelterMeasurementsRequest;
+use service::GetLatestSignalMeasurementsRequest;
+use service::GetSheltersRequest;
+use service::GetSheltersSignalsRequest;
+use service::Shelter as ShelterRepr;
+use service::ShelterMeasurement as ShelterMeasurementRepr;
+use service::Signal as SignalRepr;
+
+/// `Loaders` batch the lookups made by resolvers during a single request.
+pub struct Loaders {
+    pub shelters: BatchLoader<ShelterLoader>,
+    pub shelter_signals: BatchLoader<ShelterSignalsLoader>,
+    pub latest_shelter_measurements:
+        BatchLoader<LatestShelterMeasurementLoader>,
+    pub latest_signal_measurements: BatchLoader<LatestSignalMeasurementLoader>,
+}
+
+impl Loaders {
+    pub fn new(service: Arc<Service>, context: ServiceContext) -> Self {
+        Loaders {
+            shelters: BatchLoader::new(ShelterLoader {
+                service: service.clone(),
+                context: context.clone(),
+            }),
+            shelter_signals: BatchLoader::new(ShelterSignalsLoader {
+                service: service.clone(),
+                context: context.internal(),
+            }),
+            latest_shelter_measurements: BatchLoader::new(
+                LatestShelterMeasurementLoader {
+                    service: service.clone(),
+                    context: context.clone(),
+                },
+            ),
+            latest_signal_measurements: BatchLoader::new(
+                LatestSignalMeasurementLoader { service, context },
+            ),
+        }
+    }
+}
+
+/// A `BatchLoader` collects the keys requested concurrently by resolvers,
+/// and loads them together.
+///
+/// Its loader must return a value for every key it is given, so that keys
+/// which don't exist can be told apart from keys that weren't loaded.
+pub struct BatchLoader<T, K = Uuid>
+where
+    T: Loader<K>,
+    K: Send + Sync + Hash + Eq + Clone + 'static,
+{
+    batcher: Arc<DataLoader<T>>,
+
+    /// Loads that are in flight, which concurrent requests for the same key
+    /// wait on instead of loading it again.
+    loads: Mutex<Map<K, Load<T::Value>>>,
+}
+
+type Load<T> = Shared<BoxFuture<'static, FieldResult<T>>>;
+
+impl<T, K> BatchLoader<T, K>
+where
+    T: Loader<K, Error = FieldError>,
+    K: Send + Sync + Hash + Eq + Clone + 'static,
+    T::Value: Sync,
+{
+    pub fn new(loader: T) -> Self {
+        BatchLoader {
+            batcher: Arc::new(DataLoader::new(loader)),
+            loads: Default::default(),
+        }
+    }
+
+    pub async fn load_one(&self, key: K) -> FieldResult<T::Value> {
+        // Join the load of this key that's already in flight, if any.
+        //
+        // This is necessary because the batcher yields nothing for keys that
+        // are already part of a pending batch.
+        let load = {
+            let mut loads = self.loads.lock().unwrap();
+            let load = loads.entry(key.clone()).or_insert_with(|| {
+                let batcher = self.batcher.clone();
+                let key = key.clone();
+                async move {
+                    let mut values =
+                        batcher.load_many(once(key.clone())).await?;
+                    let value = values.remove(&key).context("missing value");
+                    value.into_field_result()
+                }
+                .boxed()
+                .shared()
+            });
+            load.clone()
+        };
+        let value = load.await;
+
+        // Forget the load once it's done, so that later requests see fresh
+        // values.
+        self.loads.lock().unwrap().remove(&key);
+        value
+    }
+}
+#[derive(Clone)]
+pub struct ShelterLoader {
+    service: Arc<Service>,
+    context: ServiceContext,
+}
+
+#[async_trait]
+impl Loader<Uuid> for ShelterLoader {
+    type Value = Option<ShelterRepr>;
+    type Error = FieldError;
+
+    async fn load(&self, keys: &[Uuid]) -> FieldResult<Map<Uuid, Self::Value>> {
+        let request = GetSheltersRequest {
+            shelter_ids: keys.to_owned(),
+        };
+        let response = self
+            .service
+            .get_shelters(&self.context, request)
+            .await
+            .into_field_result()?;
+
+        let mut shelters: Map<Uuid, Self::Value> =
+            keys.iter().map(|key| (*key, None)).collect();
+        for shelter in response.shelters {
+            shelters.insert(shelter.id, Some(shelter));
+        }
+        Ok(shelters)
+    }
+}
+
+#[derive(Clone)]
+pub struct ShelterSignalsLoader {
+    service: Arc<Service>,
+    context: ServiceContext,
+}
+
+#[async_trait]
+impl Loader<Uuid> for ShelterSignalsLoader {
+    type Value = Vec<SignalRepr>;
+    type Error = FieldError;
+
+    async fn load(&self, keys: &[Uuid]) -> FieldResult<Map<Uuid, Self::Value>> {
+        let request = GetSheltersSignalsRequest {
+            shelter_ids: keys.to_owned(),
+            include_archived: false,
+        };
+        let response = self
+            .service
+            .get_shelters_signals(&self.context, request)
+            .await
+            .into_field_result()?;
+
+        // Shelters without signals still map to an (empty) value.
+        let mut signals: Map<Uuid, Self::Value> =
+            keys.iter().map(|key| (*key, Vec::new())).collect();
+        for signal in response.signals {
+            let shelter_signals = signals.entry(signal.shelter_id).or_default();
+            shelter_signals.push(signal);
+        }
+        Ok(signals)
+    }
+}
+
+#[derive(Clone)]
+pub struct LatestShelterMeasurementLoader {
+    service: Arc<Service>,
+    context: ServiceContext,
+}
+
+#[async_trait]
+impl Loader<Uuid> for LatestShelterMeasurementLoader {
+    type Value = Option<ShelterMeasurementRepr>;
+    type Error = FieldError;
+
+    async fn load(&self, keys: &[Uuid]) -> FieldResult<Map<Uuid, Self::Value>> {
+        let request = GetLatestShelterMeasurementsRequest {
+            shelter_ids: keys.to_owned(),
+        };
+        let response = self
+            .service
+            .get_latest_shelter_measurements(&self.context, request)
+            .await
+            .into_field_result()?;
+
+        // Shelters without measurements still map to a (missing) value.
+        let mut measurements: Map<Uuid, Self::Value> =
+            keys.iter().map(|key| (*key, None)).collect();
+        for measurement in response.measurements {
+            measurements.insert(measurement.shelter_id, Some(measurement));
+        }
+        Ok(measurements)
+    }
+}
+
+#[derive(Clone)]
+pub struct LatestSignalMeasurementLoader {
+    service: Arc<Service>,
+    context: ServiceContext,
+}
+
+#[async_trait]
+impl Loader<Uuid> for LatestSignalMeasurementLoader {
+    type Value = Option<ShelterMeasurementRepr>;
+    type Error = FieldError;
+
+    async fn load(&self, keys: &[Uuid]) -> FieldResult<Map<Uuid, Self::Value>> {
+        let request = GetLatestSignalMeasurementsRequest {
+            signal_ids: keys.to_owned(),
+        };
+        let response = self
+            .service
+            .get_latest_signal_measurements(&self.context, request)
+            .await
+            .into_field_result()?;
+
+        // Signals without measurements still map to a (missing) value.
+        let mut measurements: Map<Uuid, Self::Value> =
+            keys.iter().map(|key| (*key, None)).collect();
+        for measurement in response.measurements {
+            if let Some(signal_id) = measurement.signal_id {
+                measurements.insert(signal_id, Some(measurement));
+            }
+        }
+        Ok(measurements)
+    }
+}
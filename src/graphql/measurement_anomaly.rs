@@ -0,0 +1,282 @@
+use super::prelude::*;
+
+use service::MeasurementAnomaly as MeasurementAnomalyRepr;
+use service::MeasurementAnomalyKind as MeasurementAnomalyKindRepr;
+
+use service::CountMeasurementAnomaliesRequest;
+use service::GetShelterMeasurementRequest;
+use service::GetSignalProfileRequest;
+use service::GetUserRequest;
+use service::ListMeasurementAnomaliesRequest;
+use service::ResolveMeasurementAnomalyRequest;
+
+#[derive(Debug, Clone)]
+pub struct MeasurementAnomaly(MeasurementAnomalyRepr);
+
+impl From<MeasurementAnomalyRepr> for MeasurementAnomaly {
+    fn from(anomaly: MeasurementAnomalyRepr) -> Self {
+        Self(anomaly)
+    }
+}
+
+/// A `MeasurementAnomaly` flags a `ShelterMeasurement` that doesn't look like
+/// a genuine reading, for an admin to review.
+#[Object]
+impl MeasurementAnomaly {
+    async fn id(&self) -> Id {
+        Id::new::<Self>(self.0.id)
+    }
+
+    async fn created_at(&self) -> &DateTime {
+        &self.0.created_at
+    }
+
+    async fn updated_at(&self) -> &DateTime {
+        &self.0.updated_at
+    }
+
+    async fn kind(&self) -> MeasurementAnomalyKind {
+        self.0.kind.into()
+    }
+
+    /// How far the `ShelterMeasurement` exceeded the threshold for its kind
+    /// of anomaly, such that `1.0` is just at the threshold.
+    async fn score(&self) -> f64 {
+        self.0.score
+    }
+
+    async fn measurement(
+        &self,
+        ctx: &Context<'_>,
+    ) -> FieldResult<ShelterMeasurement> {
+        let (service, context) = get_service(ctx);
+
+        // Request measurement from service.
+        let measurement = {
+            let request = GetShelterMeasurementRequest {
+                measurement_id: self.0.measurement_id,
+            };
+            let response = service
+                .get_shelter_measurement(context, request)
+                .await
+                .into_field_result()?;
+            response.measurement.context("measurement not found")?
+        };
+
+        // Return measurement object.
+        Ok(measurement.into())
+    }
+
+    async fn shelter(&self, ctx: &Context<'_>) -> FieldResult<Shelter> {
+        let loaders = get_loaders(ctx);
+
+        // Load shelter.
+        let shelter = loaders
+            .shelters
+            .load_one(self.0.shelter_id)
+            .await?
+            .context("shelter not found")?;
+
+        // Return shelter object.
+        Ok(shelter.into())
+    }
+
+    async fn signal(&self, ctx: &Context<'_>) -> FieldResult<Signal> {
+        let (service, context) = get_service(ctx);
+
+        // Request profile from service.
+        let profile = {
+            let request = GetSignalProfileRequest {
+                signal_id: self.0.signal_id,
+            };
+            let response = service
+                .get_signal_profile(context, request)
+                .await
+                .into_field_result()?;
+            response.profile.context("signal not found")?
+        };
+
+        // Return signal object.
+        Ok(profile.into())
+    }
+
+    /// When an admin resolved the anomaly, if it has been resolved.
+    async fn resolved_at(&self) -> Option<&DateTime> {
+        self.0.resolved_at.as_ref()
+    }
+
+    /// The admin who resolved the anomaly.
+    async fn resolved_by(
+        &self,
+        ctx: &Context<'_>,
+    ) -> FieldResult<Option<User>> {
+        let user_id = match self.0.resolved_by_id {
+            Some(user_id) => user_id,
+            None => return Ok(None),
+        };
+        let (service, context) = get_service(ctx);
+
+        // Request user from service.
+        let user = {
+            let request = GetUserRequest { user_id };
+            let response = service
+                .get_user(context, request)
+                .await
+                .into_field_result()?;
+            response.user
+        };
+
+        // Return user object.
+        Ok(user.map(Into::into))
+    }
+}
+
+/// How a `ShelterMeasurement` is anomalous.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Enum)]
+pub enum MeasurementAnomalyKind {
+    /// The reading changed by much of the `Shelter`'s capacity within a few
+    /// minutes.
+    Spike,
+
+    /// The reading has been the same for over a day.
+    Flatline,
+
+    /// The reading differs greatly from the `Signal`'s readings at the same
+    /// hour of the week in previous weeks.
+    Atypical,
+}
+
+impl From<MeasurementAnomalyKindRepr> for MeasurementAnomalyKind {
+    fn from(kind: MeasurementAnomalyKindRepr) -> Self {
+        use MeasurementAnomalyKind::*;
+        use MeasurementAnomalyKindRepr as Repr;
+        match kind {
+            Repr::Spike => Spike,
+            Repr::Flatline => Flatline,
+            Repr::Atypical => Atypical,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct MeasurementAnomalyQueries;
+
+#[Object]
+impl MeasurementAnomalyQueries {
+    /// List `MeasurementAnomaly`s, newest first.
+    #[allow(clippy::too_many_arguments)]
+    async fn measurement_anomalies(
+        &self,
+        ctx: &Context<'_>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "Only list `MeasurementAnomaly`s after this cursor.")]
+        after: Option<Cursor>,
+
+        #[rustfmt::skip]
+        #[graphql(
+            desc = "Only list `MeasurementAnomaly`s before this cursor."
+        )]
+        before: Option<Cursor>,
+
+        #[rustfmt::skip]
+        #[graphql(
+            desc = "The number of leading `MeasurementAnomaly`s to fetch."
+        )]
+        first: Option<u32>,
+
+        #[rustfmt::skip]
+        #[graphql(
+            desc = "The number of trailing `MeasurementAnomaly`s to fetch."
+        )]
+        last: Option<u32>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "Whether to include resolved `MeasurementAnomaly`s.")]
+        include_resolved: Option<bool>,
+    ) -> FieldResult<Connection<MeasurementAnomaly>> {
+        let include_resolved = include_resolved.unwrap_or_default();
+
+        let (service, context) = get_service(ctx);
+
+        // Count matching anomalies.
+        let total = {
+            let request = CountMeasurementAnomaliesRequest { include_resolved };
+            let response = service
+                .count_measurement_anomalies(context, request)
+                .await
+                .into_field_result()?;
+            response.count
+        };
+
+        // Determine which anomalies to fetch.
+        let window = ConnectionWindow::new(after, before, first, last, total)
+            .into_field_result()?;
+
+        // Request anomalies from service.
+        let anomalies = {
+            let request = ListMeasurementAnomaliesRequest {
+                limit: window.limit(),
+                offset: window.offset(),
+                include_resolved,
+            };
+            let response = service
+                .list_measurement_anomalies(context, request)
+                .await
+                .into_field_result()?;
+            response.anomalies
+        };
+
+        Ok(window.connect(anomalies))
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct MeasurementAnomalyMutations;
+
+#[derive(Debug, Clone, InputObject)]
+pub struct ResolveMeasurementAnomalyInput {
+    pub anomaly_id: Id,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ResolveMeasurementAnomalyPayload {
+    pub anomaly: MeasurementAnomaly,
+}
+
+#[Object]
+impl MeasurementAnomalyMutations {
+    /// Mark a `MeasurementAnomaly` as reviewed.
+    async fn resolve_measurement_anomaly(
+        &self,
+        ctx: &Context<'_>,
+        input: ResolveMeasurementAnomalyInput,
+    ) -> FieldResult<ResolveMeasurementAnomalyPayload> {
+        let ResolveMeasurementAnomalyInput { anomaly_id } = input;
+
+        // Parse anomaly ID.
+        let anomaly_id = anomaly_id
+            .get::<MeasurementAnomaly>()
+            .context("invalid anomaly ID")
+            .into_field_result()?;
+
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Resolve anomaly.
+        let anomaly = {
+            let request = ResolveMeasurementAnomalyRequest { anomaly_id };
+            let response = service
+                .resolve_measurement_anomaly(context, request)
+                .await
+                .into_field_result()?;
+            response.anomaly
+        };
+
+        // Respond with payload.
+        let payload = ResolveMeasurementAnomalyPayload {
+            anomaly: anomaly.into(),
+        };
+        Ok(payload)
+    }
+}
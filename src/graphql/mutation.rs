@@ -1,10 +1,24 @@
 use super::prelude::*;
 #[derive(Debug, Clone, MergedObject)]
-pub struct Mutation(ShelterMutations, SignalMutations, UserMutations);
+pub struct Mutation(
+    MeasurementAnomalyMutations,
+    ShelterMutations,
+    ShelterMeasurementMutations,
+    ShelterMembershipMutations,
+    SignalMutations,
+    UserMutations,
+);
 
 impl Mutation {
     pub fn new() -> Self {
-        Self(ShelterMutations, SignalMutations, UserMutations)
+        Self(
+            MeasurementAnomalyMutations,
+            ShelterMutations,
+            ShelterMeasurementMutations,
+            ShelterMembershipMutations,
+            SignalMutations,
+            UserMutations,
+        )
     }
 }
 
@@ -0,0 +1,109 @@
+use super::prelude::*;
+
+use futures_util::future::join_all;
+
+use service::GetShelterMeasurementRequest;
+use service::GetSignalProfileRequest;
+use service::GetUserRequest;
+
+/// The maximum number of `Node`s that can be fetched at once.
+const MAX_NODES: usize = 100;
+
+/// A `Node` is an object with a globally unique `ID`.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Interface)]
+#[graphql(field(name = "id", type = "Id"))]
+pub enum Node {
+    Shelter(Shelter),
+    ShelterMeasurement(ShelterMeasurement),
+    Signal(Signal),
+    User(User),
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct NodeQueries;
+
+#[Object]
+impl NodeQueries {
+    /// Get a `Node` by its `ID`.
+    async fn node(
+        &self,
+        ctx: &Context<'_>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "The `ID` of the `Node` to fetch.")]
+        id: Id,
+    ) -> FieldResult<Option<Node>> {
+        get_node(ctx, &id).await
+    }
+
+    /// Get `Node`s by their `ID`s, in the same order. Each `Node` that
+    /// doesn't exist, or that can't be viewed, is `null`.
+    async fn nodes(
+        &self,
+        ctx: &Context<'_>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "The `ID`s of the `Node`s to fetch.")]
+        ids: Vec<Id>,
+    ) -> FieldResult<Vec<Option<Node>>> {
+        if ids.len() > MAX_NODES {
+            let message = format!("cannot fetch more than {} nodes", MAX_NODES);
+            return Err(FieldError::new(message));
+        }
+
+        // Fetch nodes, such that one that fails doesn't fail the others.
+        let nodes = ids.iter().map(|id| async move {
+            get_node(ctx, id).await.unwrap_or_else(|error| {
+                debug!("failed to get node {}: {}", id, error.message);
+                None
+            })
+        });
+        let nodes = join_all(nodes).await;
+        Ok(nodes)
+    }
+}
+
+/// Fetch the `Node` identified by `id`, dispatching on its type name.
+async fn get_node(ctx: &Context<'_>, id: &Id) -> FieldResult<Option<Node>> {
+    let (service, context) = get_service(ctx);
+
+    let node: Option<Node> = if id.is::<Shelter>() {
+        let shelter_id = id.get::<Shelter>().into_field_result()?;
+        let loaders = get_loaders(ctx);
+        let shelter = loaders.shelters.load_one(shelter_id).await?;
+        shelter.map(Shelter::from).map(Into::into)
+    } else if id.is::<ShelterMeasurement>() {
+        let measurement_id =
+            id.get::<ShelterMeasurement>().into_field_result()?;
+        let request = GetShelterMeasurementRequest { measurement_id };
+        let response = service
+            .get_shelter_measurement(context, request)
+            .await
+            .into_field_result()?;
+        response
+            .measurement
+            .map(ShelterMeasurement::from)
+            .map(Into::into)
+    } else if id.is::<Signal>() {
+        let signal_id = id.get::<Signal>().into_field_result()?;
+        let request = GetSignalProfileRequest { signal_id };
+        let response = service
+            .get_signal_profile(context, request)
+            .await
+            .into_field_result()?;
+        response.profile.map(Signal::from).map(Into::into)
+    } else if id.is::<User>() {
+        let user_id = id.get::<User>().into_field_result()?;
+        let request = GetUserRequest { user_id };
+        let response = service
+            .get_user(context, request)
+            .await
+            .into_field_result()?;
+        response.user.map(User::from).map(Into::into)
+    } else {
+        return Err(FieldError::new("unknown node type"));
+    };
+
+    Ok(node)
+}
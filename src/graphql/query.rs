@@ -2,7 +2,9 @@ use super::prelude::*;
 
 #[derive(Debug, Clone, Hash, MergedObject)]
 pub struct Query(
+    MeasurementAnomalyQueries,
     MetaQueries,
+    NodeQueries,
     ShelterQueries,
     ShelterMeasurementQueries,
     SignalQueries,
@@ -12,7 +14,9 @@ pub struct Query(
 impl Query {
     pub fn new() -> Self {
         Query(
+            MeasurementAnomalyQueries,
             MetaQueries,
+            NodeQueries,
             ShelterQueries,
             ShelterMeasurementQueries,
             SignalQueries,
@@ -2,20 +2,36 @@ use super::prelude::*;
 
 use service::Slug;
 
+use service::ShelterMeasurementFilter;
+
+use service::NearbyShelter as NearbyShelterRepr;
 use service::Shelter as ShelterRepr;
+use service::ShelterAggregation as ShelterAggregationRepr;
+use service::ShelterCapacityPolicy as ShelterCapacityPolicyRepr;
+use service::ShelterFilter as ShelterFilterRepr;
 use service::ShelterFood as ShelterFoodRepr;
-use service::ShelterMeasurement as ShelterMeasurementRepr;
+use service::ShelterOccupancyEvent as ShelterOccupancyEventRepr;
+use service::ShelterSort as ShelterSortRepr;
+use service::ShelterSortField as ShelterSortFieldRepr;
 use service::ShelterSpace as ShelterSpaceRepr;
 use service::ShelterTag as ShelterTagRepr;
+use service::ShelterVacancy as ShelterVacancyRepr;
+use service::SortDirection as SortDirectionRepr;
 
+use service::CountShelterMeasurementsRequest;
+use service::CountSheltersRequest;
 use service::CreateShelterRequest;
 use service::DeleteShelterRequest;
 use service::GetShelterBySlugRequest;
 use service::GetShelterRequest;
 use service::GetShelterSignalsRequest;
 use service::ListShelterMeasurementsRequest;
+use service::ListShelterMembershipsRequest;
+use service::ListSheltersNearRequest;
 use service::ListSheltersRequest;
+use service::UpdateShelterOccupancyRequest;
 use service::UpdateShelterRequest;
+use service::WatchShelterOccupancyRequest;
 
 #[derive(Debug, Clone)]
 pub struct Shelter(ShelterRepr);
@@ -29,7 +45,7 @@ impl From<ShelterRepr> for Shelter {
 /// A `Shelter` is a temporary residence for people without a home.
 #[Object]
 impl Shelter {
-    async fn id(&self) -> Id {
+    pub async fn id(&self) -> Id {
         Id::new::<Self>(self.0.id)
     }
 
@@ -79,33 +95,45 @@ impl Shelter {
         capacity.into()
     }
 
-    async fn occupancy(&self, ctx: &Context<'_>) -> FieldResult<ShelterSpace> {
-        let (service, context) = get_service(ctx);
+    /// How readings that exceed the `Shelter`'s capacity are handled.
+    async fn capacity_policy(&self) -> ShelterCapacityPolicy {
+        self.0.capacity_policy.into()
+    }
 
-        let measurements = {
-            let context = context.internal();
-            let request = ListShelterMeasurementsRequest {
-                shelter_id: self.0.id,
-                limit: 1,
-                offset: 0,
-            };
-            let response = service
-                .list_shelter_measurements(&context, request)
-                .await
-                .into_field_result()?;
-            response.measurements
-        };
-        let measurement = measurements.into_iter().next();
-        let measurement = if let Some(measurement) = measurement {
-            measurement
-        } else {
-            let occupancy = ShelterSpace { beds: 0, spots: 0 };
-            return Ok(occupancy);
-        };
+    async fn occupancy(&self) -> ShelterSpace {
+        let occupancy = self.0.occupancy.to_owned().unwrap_or_default();
+        occupancy.into()
+    }
 
-        let ShelterMeasurementRepr { occupancy, .. } = measurement;
-        let occupancy: ShelterSpace = occupancy.into();
-        Ok(occupancy)
+    /// When the `Shelter`'s occupancy was last measured by a `Signal`.
+    async fn occupancy_updated_at(&self) -> Option<&DateTime> {
+        self.0.occupancy_updated_at.as_ref()
+    }
+
+    /// How the readings of the `Shelter`'s `Signal`s are combined into its
+    /// occupancy.
+    async fn aggregation(&self) -> ShelterAggregation {
+        self.0.aggregation.into()
+    }
+
+    /// The `Signal` whose readings determine the `Shelter`'s occupancy, under
+    /// the `PRIMARY` aggregation.
+    async fn primary_signal(
+        &self,
+        ctx: &Context<'_>,
+    ) -> FieldResult<Option<Signal>> {
+        let primary_signal_id = match self.0.primary_signal_id {
+            Some(signal_id) => signal_id,
+            None => return Ok(None),
+        };
+        let loaders = get_loaders(ctx);
+
+        // Load corresponding signals.
+        let signals = loaders.shelter_signals.load_one(self.0.id).await?;
+        let signal = signals
+            .into_iter()
+            .find(|signal| signal.id == primary_signal_id);
+        Ok(signal.map(Into::into))
     }
 
     async fn food(&self) -> ShelterFood {
@@ -117,66 +145,150 @@ impl Shelter {
         tags.into_iter().map(Into::into).collect()
     }
 
-    async fn signals(&self, ctx: &Context<'_>) -> FieldResult<Vec<Signal>> {
-        // Get service.
-        let (service, context) = get_service(ctx);
+    async fn signals(
+        &self,
+        ctx: &Context<'_>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "Whether to include archived `Signal`s.")]
+        include_archived: Option<bool>,
+    ) -> FieldResult<Vec<Signal>> {
+        let signals = if include_archived.unwrap_or_default() {
+            let (service, context) = get_service(ctx);
 
-        // Get corresponding signals.
-        let signals = {
-            let context = context.internal();
+            // Request signals from service, including archived signals.
             let request = GetShelterSignalsRequest {
                 shelter_id: self.0.id,
+                include_archived: true,
             };
             let response = service
-                .get_shelter_signals(&context, request)
+                .get_shelter_signals(context, request)
                 .await
                 .into_field_result()?;
             response.signals
+        } else {
+            let loaders = get_loaders(ctx);
+
+            // Load corresponding signals.
+            loaders.shelter_signals.load_one(self.0.id).await?
         };
 
         let signals = signals.into_iter().map(Into::into).collect();
         Ok(signals)
     }
 
+    async fn memberships(
+        &self,
+        ctx: &Context<'_>,
+    ) -> FieldResult<Vec<ShelterMembership>> {
+        let (service, context) = get_service(ctx);
+
+        // Request memberships from service.
+        let memberships = {
+            let request = ListShelterMembershipsRequest {
+                shelter_id: self.0.id,
+            };
+            let response = service
+                .list_shelter_memberships(context, request)
+                .await
+                .into_field_result()?;
+            response.memberships
+        };
+
+        let memberships = memberships.into_iter().map(Into::into).collect();
+        Ok(memberships)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn measurements(
         &self,
         ctx: &Context<'_>,
 
-        // TODO: Use `default` instead of `default_with` once
-        // https://github.com/async-graphql/async-graphql/issues/361
-        // is resolved.
+        #[rustfmt::skip]
+        #[graphql(desc = "Only list `ShelterMeasurement`s after this cursor.")]
+        after: Option<Cursor>,
+
         #[rustfmt::skip]
         #[graphql(
-            desc = "The maximum number of `ShelterMeasurement`s to fetch.",
-            default_with = "25"
+            desc = "Only list `ShelterMeasurement`s before this cursor."
         )]
-        limit: u32,
+        before: Option<Cursor>,
 
         #[rustfmt::skip]
         #[graphql(
-            desc = "The number of initial `ShelterMeasurement`s to skip.",
-            default
+            desc = "The number of leading `ShelterMeasurement`s to fetch."
         )]
-        offset: u32,
-    ) -> FieldResult<Vec<ShelterMeasurement>> {
+        first: Option<u32>,
+
+        #[rustfmt::skip]
+        #[graphql(
+            desc = "The number of trailing `ShelterMeasurement`s to fetch."
+        )]
+        last: Option<u32>,
+
+        #[rustfmt::skip]
+        #[graphql(
+            desc = "Only list `ShelterMeasurement`s taken at or after this time."
+        )]
+        since: Option<DateTime>,
+
+        #[rustfmt::skip]
+        #[graphql(
+            desc = "Only list `ShelterMeasurement`s taken before this time."
+        )]
+        until: Option<DateTime>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "Whether to list retracted `ShelterMeasurement`s.")]
+        include_retracted: Option<bool>,
+    ) -> FieldResult<Connection<ShelterMeasurement>> {
+        let filter = ShelterMeasurementFilter {
+            since,
+            until,
+            include_retracted: include_retracted.unwrap_or_default(),
+        };
+        let window = KeysetWindow::new(after, before, first, last)
+            .into_field_result()?;
+
         let (service, context) = get_service(ctx);
+        let context = context.internal();
 
-        let measurements = {
-            let context = context.internal();
+        // Count measurements, which requires a scan of the matching history.
+        let total = if ctx.look_ahead().field("totalCount").exists() {
+            let request = CountShelterMeasurementsRequest {
+                shelter_id: self.0.id,
+                filter: filter.clone(),
+            };
+            let response = service
+                .count_shelter_measurements(&context, request)
+                .await
+                .into_field_result()?;
+            response.count
+        } else {
+            0
+        };
+
+        let (measurements, has_more) = {
             let request = ListShelterMeasurementsRequest {
                 shelter_id: self.0.id,
-                limit,
-                offset,
+                filter,
+                after: window.after.map(Into::into),
+                before: window.before.map(Into::into),
+                limit: window.limit,
+                from_end: window.from_end,
             };
             let response = service
                 .list_shelter_measurements(&context, request)
                 .await
                 .into_field_result()?;
-            response.measurements
+            (response.measurements, response.has_more)
         };
 
-        let measurements = measurements.into_iter().map(Into::into).collect();
-        Ok(measurements)
+        let connection =
+            window.connect(measurements, has_more, total, |measurement| {
+                (measurement.created_at, measurement.id)
+            });
+        Ok(connection)
     }
 }
 
@@ -211,6 +323,91 @@ impl From<ShelterFoodRepr> for ShelterFood {
     }
 }
 
+/// How readings that exceed a `Shelter`'s capacity are handled.
+///
+/// Where readings are aggregated into an occupancy that exceeds capacity, the
+/// occupancy is clamped, except that under `REJECT` a reading reported as it
+/// happens is refused instead.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Enum)]
+pub enum ShelterCapacityPolicy {
+    /// Refuse the reading.
+    Reject,
+
+    /// Reduce the reading to the `Shelter`'s capacity.
+    Clamp,
+
+    /// Record the reading as flagged, without it affecting the `Shelter`'s
+    /// occupancy.
+    Flag,
+}
+
+impl From<ShelterCapacityPolicy> for ShelterCapacityPolicyRepr {
+    fn from(policy: ShelterCapacityPolicy) -> Self {
+        use ShelterCapacityPolicy::*;
+        use ShelterCapacityPolicyRepr as Repr;
+        match policy {
+            Reject => Repr::Reject,
+            Clamp => Repr::Clamp,
+            Flag => Repr::Flag,
+        }
+    }
+}
+
+impl From<ShelterCapacityPolicyRepr> for ShelterCapacityPolicy {
+    fn from(policy: ShelterCapacityPolicyRepr) -> Self {
+        use ShelterCapacityPolicy::*;
+        use ShelterCapacityPolicyRepr as Repr;
+        match policy {
+            Repr::Reject => Reject,
+            Repr::Clamp => Clamp,
+            Repr::Flag => Flag,
+        }
+    }
+}
+
+/// How the readings of a `Shelter`'s `Signal`s are combined into its
+/// occupancy.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Enum)]
+pub enum ShelterAggregation {
+    /// Use the most recent reading of any `Signal`.
+    Latest,
+
+    /// Add up the latest reading of each `Signal`.
+    Sum,
+
+    /// Use the largest of the latest readings of each `Signal`.
+    Max,
+
+    /// Use the latest reading of the primary `Signal`.
+    Primary,
+}
+
+impl From<ShelterAggregation> for ShelterAggregationRepr {
+    fn from(aggregation: ShelterAggregation) -> Self {
+        use ShelterAggregation::*;
+        use ShelterAggregationRepr as Repr;
+        match aggregation {
+            Latest => Repr::Latest,
+            Sum => Repr::Sum,
+            Max => Repr::Max,
+            Primary => Repr::Primary,
+        }
+    }
+}
+
+impl From<ShelterAggregationRepr> for ShelterAggregation {
+    fn from(aggregation: ShelterAggregationRepr) -> Self {
+        use ShelterAggregation::*;
+        use ShelterAggregationRepr as Repr;
+        match aggregation {
+            Repr::Latest => Latest,
+            Repr::Sum => Sum,
+            Repr::Max => Max,
+            Repr::Primary => Primary,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Enum)]
 pub enum ShelterTag {
     Adult,
@@ -280,6 +477,142 @@ impl From<ShelterSpaceInput> for ShelterSpaceRepr {
     }
 }
 
+/// A `NearbyShelter` is a `Shelter` found by a location-based search.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct NearbyShelter {
+    pub shelter: Shelter,
+
+    /// The distance to the `Shelter`, in kilometers.
+    pub distance: f64,
+}
+
+impl From<NearbyShelterRepr> for NearbyShelter {
+    fn from(nearby: NearbyShelterRepr) -> Self {
+        let NearbyShelterRepr { shelter, distance } = nearby;
+        Self {
+            shelter: shelter.into(),
+            distance,
+        }
+    }
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct ShelterFilterInput {
+    /// Only include `Shelter`s with all of these tags.
+    pub tags: Option<Set<ShelterTag>>,
+
+    /// Only include `Shelter`s with any of these food options.
+    pub food: Option<Set<ShelterFood>>,
+
+    /// Only include `Shelter`s in this city.
+    pub city: Option<String>,
+
+    /// Only include `Shelter`s in this region.
+    pub region: Option<String>,
+
+    /// Only include `Shelter`s with this kind of free space.
+    pub vacancy: Option<ShelterVacancy>,
+}
+
+impl From<ShelterFilterInput> for ShelterFilterRepr {
+    fn from(filter: ShelterFilterInput) -> Self {
+        let ShelterFilterInput {
+            tags,
+            food,
+            city,
+            region,
+            vacancy,
+        } = filter;
+
+        let tags = tags.unwrap_or_default();
+        let food = food.unwrap_or_default();
+        Self {
+            tags: tags.into_iter().map(Into::into).collect(),
+            food: food.into_iter().map(Into::into).collect(),
+            city,
+            region,
+            vacancy: vacancy.map(Into::into),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Enum)]
+pub enum ShelterVacancy {
+    /// A free spot or a free bed.
+    Any,
+    Spots,
+    Beds,
+}
+
+impl From<ShelterVacancy> for ShelterVacancyRepr {
+    fn from(vacancy: ShelterVacancy) -> Self {
+        use ShelterVacancy::*;
+        use ShelterVacancyRepr as Repr;
+        match vacancy {
+            Any => Repr::Any,
+            Spots => Repr::Spots,
+            Beds => Repr::Beds,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, InputObject)]
+pub struct ShelterSortInput {
+    pub field: ShelterSortField,
+
+    #[graphql(default_with = "SortDirection::Ascending")]
+    pub direction: SortDirection,
+}
+
+impl From<ShelterSortInput> for ShelterSortRepr {
+    fn from(sort: ShelterSortInput) -> Self {
+        let ShelterSortInput { field, direction } = sort;
+        Self {
+            field: field.into(),
+            direction: direction.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Enum)]
+pub enum ShelterSortField {
+    Name,
+
+    /// The total number of free spots and beds.
+    Availability,
+
+    UpdatedAt,
+}
+
+impl From<ShelterSortField> for ShelterSortFieldRepr {
+    fn from(field: ShelterSortField) -> Self {
+        use ShelterSortField::*;
+        use ShelterSortFieldRepr as Repr;
+        match field {
+            Name => Repr::Name,
+            Availability => Repr::Availability,
+            UpdatedAt => Repr::UpdatedAt,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Enum)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl From<SortDirection> for SortDirectionRepr {
+    fn from(direction: SortDirection) -> Self {
+        use SortDirection::*;
+        use SortDirectionRepr as Repr;
+        match direction {
+            Ascending => Repr::Ascending,
+            Descending => Repr::Descending,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Hash)]
 pub struct ShelterQueries;
 
@@ -343,10 +676,95 @@ impl ShelterQueries {
     }
 
     /// List all registered `Shelter`s.
+    #[allow(clippy::too_many_arguments)]
     async fn shelters(
         &self,
         ctx: &Context<'_>,
 
+        #[rustfmt::skip]
+        #[graphql(desc = "Only list `Shelter`s after this cursor.")]
+        after: Option<Cursor>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "Only list `Shelter`s before this cursor.")]
+        before: Option<Cursor>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "The number of leading `Shelter`s to fetch.")]
+        first: Option<u32>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "The number of trailing `Shelter`s to fetch.")]
+        last: Option<u32>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "Criteria that listed `Shelter`s must match.")]
+        filter: Option<ShelterFilterInput>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "The order in which to list `Shelter`s.")]
+        sort: Option<ShelterSortInput>,
+    ) -> FieldResult<Connection<Shelter>> {
+        let filter: ShelterFilterRepr =
+            filter.map(Into::into).unwrap_or_default();
+        let sort = sort.map(Into::into).unwrap_or_default();
+
+        let (service, context) = get_service(ctx);
+
+        // Count matching shelters.
+        let total = {
+            let request = CountSheltersRequest {
+                filter: filter.clone(),
+            };
+            let response = service
+                .count_shelters(context, request)
+                .await
+                .into_field_result()?;
+            response.count
+        };
+
+        // Determine which shelters to fetch.
+        let window = ConnectionWindow::new(after, before, first, last, total)
+            .into_field_result()?;
+
+        // Request shelters from service.
+        let shelters = {
+            let request = ListSheltersRequest {
+                filter,
+                sort,
+                limit: window.limit(),
+                offset: window.offset(),
+            };
+            let response = service
+                .list_shelters(context, request)
+                .await
+                .into_field_result()?;
+            response.shelters
+        };
+
+        Ok(window.connect(shelters))
+    }
+
+    /// List the `Shelter`s closest to a `Coordinate`, nearest first.
+    async fn shelters_near(
+        &self,
+        ctx: &Context<'_>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "The `Coordinate` to search around.")]
+        coordinate: Coordinate,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "The search radius, in kilometers.")]
+        radius_km: f64,
+
+        #[rustfmt::skip]
+        #[graphql(
+            desc = "Only include `Shelter`s with a free spot or bed.",
+            default
+        )]
+        only_available: bool,
+
         // TODO: Use `default` instead of `default_with` once
         // https://github.com/async-graphql/async-graphql/issues/361
         // is resolved.
@@ -356,29 +774,110 @@ impl ShelterQueries {
             default_with = "25"
         )]
         limit: u32,
-
-        #[rustfmt::skip]
-        #[graphql(desc = "The number of initial `Shelter`s to skip.", default)]
-        offset: u32,
-    ) -> FieldResult<Vec<Shelter>> {
+    ) -> FieldResult<Vec<NearbyShelter>> {
         let (service, context) = get_service(ctx);
 
-        // Request shelters from service.
+        // Request nearby shelters from service.
         let shelters = {
-            let request = ListSheltersRequest { limit, offset };
+            let request = ListSheltersNearRequest {
+                coordinate: coordinate.into(),
+                radius_km,
+                only_available,
+                limit,
+            };
             let response = service
-                .list_shelters(context, request)
+                .list_shelters_near(context, request)
                 .await
                 .into_field_result()?;
             response.shelters
         };
 
-        // Return shelter object.
         let shelters = shelters.into_iter().map(Into::into).collect();
         Ok(shelters)
     }
 }
 
+#[derive(Debug, Clone, Hash)]
+pub struct ShelterSubscriptions;
+
+/// A `ShelterOccupancyEvent` describes an update to a `Shelter`'s occupancy.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ShelterOccupancyEvent {
+    pub shelter: Shelter,
+    pub measurement: ShelterMeasurement,
+}
+
+impl From<ShelterOccupancyEventRepr> for ShelterOccupancyEvent {
+    fn from(event: ShelterOccupancyEventRepr) -> Self {
+        let ShelterOccupancyEventRepr {
+            shelter,
+            measurement,
+        } = event;
+
+        Self {
+            shelter: shelter.into(),
+            measurement: measurement.into(),
+        }
+    }
+}
+
+#[Subscription]
+impl ShelterSubscriptions {
+    /// Watch for occupancy updates to a particular `Shelter`.
+    async fn shelter_occupancy_changed(
+        &self,
+        ctx: &Context<'_>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "The `ID` of the `Shelter` to watch.")]
+        shelter_id: Id,
+    ) -> FieldResult<impl Stream<Item = ShelterOccupancyEvent>> {
+        // Parse shelter ID.
+        let shelter_id = shelter_id
+            .get::<Shelter>()
+            .context("invalid shelter ID")
+            .into_field_result()?;
+
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Watch shelter occupancy in service.
+        let events = {
+            let request = WatchShelterOccupancyRequest {
+                shelter_id: Some(shelter_id),
+            };
+            let response = service
+                .watch_shelter_occupancy(context, request)
+                .await
+                .into_field_result()?;
+            response.events
+        };
+
+        Ok(events.map(Into::into))
+    }
+
+    /// Watch for occupancy updates to any `Shelter`.
+    async fn occupancy_changed(
+        &self,
+        ctx: &Context<'_>,
+    ) -> FieldResult<impl Stream<Item = ShelterOccupancyEvent>> {
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Watch shelter occupancy in service.
+        let events = {
+            let request = WatchShelterOccupancyRequest { shelter_id: None };
+            let response = service
+                .watch_shelter_occupancy(context, request)
+                .await
+                .into_field_result()?;
+            response.events
+        };
+
+        Ok(events.map(Into::into))
+    }
+}
+
 #[derive(Debug, Clone, Hash)]
 pub struct ShelterMutations;
 
@@ -414,8 +913,11 @@ pub struct UpdateShelterInput {
     pub address: Option<AddressInput>,
     pub location: Option<Coordinate>,
     pub capacity: Option<ShelterSpaceInput>,
+    pub capacity_policy: Option<ShelterCapacityPolicy>,
     pub food: Option<ShelterFood>,
     pub tags: Option<Set<ShelterTag>>,
+    pub aggregation: Option<ShelterAggregation>,
+    pub primary_signal_id: Option<Id>,
 }
 
 #[derive(Debug, Clone, SimpleObject)]
@@ -423,6 +925,19 @@ pub struct UpdateShelterPayload {
     pub shelter: Shelter,
 }
 
+#[derive(Debug, Clone, InputObject)]
+pub struct UpdateShelterOccupancyInput {
+    pub shelter_id: Id,
+    pub spots: u16,
+    pub beds: u16,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct UpdateShelterOccupancyPayload {
+    pub shelter: Shelter,
+    pub measurement: ShelterMeasurement,
+}
+
 #[derive(Debug, Clone, InputObject)]
 pub struct DeleteShelterInput {
     pub shelter_id: Id,
@@ -533,8 +1048,11 @@ impl ShelterMutations {
             address,
             location,
             capacity,
+            capacity_policy,
             food,
             tags,
+            aggregation,
+            primary_signal_id,
         } = input;
 
         // Validate shelter ID.
@@ -543,6 +1061,13 @@ impl ShelterMutations {
             .context("invalid shelter ID")
             .into_field_result()?;
 
+        // Validate primary signal ID.
+        let primary_signal_id = primary_signal_id
+            .map(|id| id.get::<Signal>())
+            .transpose()
+            .context("invalid primary signal ID")
+            .into_field_result()?;
+
         // Get service.
         let (service, context) = get_service(ctx);
 
@@ -585,9 +1110,11 @@ impl ShelterMutations {
                 let location = location.map(Into::into);
 
                 let capacity = capacity.map(Into::into);
+                let capacity_policy = capacity_policy.map(Into::into);
                 let food = food.map(Into::into);
                 let tags =
                     tags.map(|tags| tags.into_iter().map(Into::into).collect());
+                let aggregation = aggregation.map(Into::into);
 
                 UpdateShelterRequest {
                     shelter_id,
@@ -600,8 +1127,11 @@ impl ShelterMutations {
                     address,
                     location,
                     capacity,
+                    capacity_policy,
                     food,
                     tags,
+                    aggregation,
+                    primary_signal_id,
                 }
             };
             let response = service
@@ -618,6 +1148,48 @@ impl ShelterMutations {
         Ok(payload)
     }
 
+    /// Report a `Shelter`'s occupancy by hand, as the viewing `User`.
+    async fn update_shelter_occupancy(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateShelterOccupancyInput,
+    ) -> FieldResult<UpdateShelterOccupancyPayload> {
+        let UpdateShelterOccupancyInput {
+            shelter_id,
+            spots,
+            beds,
+        } = input;
+
+        // Validate shelter ID.
+        let shelter_id = shelter_id
+            .get::<Shelter>()
+            .context("invalid shelter ID")
+            .into_field_result()?;
+
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Update shelter occupancy in service.
+        let (shelter, measurement) = {
+            let request = UpdateShelterOccupancyRequest {
+                shelter_id,
+                occupancy: ShelterSpaceRepr { spots, beds },
+            };
+            let response = service
+                .update_shelter_occupancy(context, request)
+                .await
+                .into_field_result()?;
+            (response.shelter, response.measurement)
+        };
+
+        // Return payload.
+        let payload = UpdateShelterOccupancyPayload {
+            shelter: shelter.into(),
+            measurement: measurement.into(),
+        };
+        Ok(payload)
+    }
+
     /// Delete a `Shelter`.
     async fn delete_shelter(
         &self,
@@ -1,7 +1,13 @@
 use super::prelude::*;
 
+use service::CorrectShelterMeasurementRequest;
 use service::GetShelterMeasurementRequest;
+use service::GetSignalProfileRequest;
+use service::GetUserRequest;
+use service::RetractShelterMeasurementRequest;
 use service::ShelterMeasurement as ShelterMeasurementRepr;
+use service::ShelterMeasurementCorrection;
+use service::ShelterMeasurementStatus as ShelterMeasurementStatusRepr;
 
 #[derive(Debug, Clone, Hash)]
 pub struct ShelterMeasurement(ShelterMeasurementRepr);
@@ -14,7 +20,7 @@ impl From<ShelterMeasurementRepr> for ShelterMeasurement {
 
 #[Object]
 impl ShelterMeasurement {
-    async fn id(&self) -> Id {
+    pub async fn id(&self) -> Id {
         Id::new::<Self>(self.0.id)
     }
 
@@ -28,9 +34,149 @@ impl ShelterMeasurement {
         occupancy.into()
     }
 
+    /// The `Signal` that reported this measurement, unless it was reported by
+    /// a `User`.
+    async fn signal(&self, ctx: &Context<'_>) -> FieldResult<Option<Signal>> {
+        let signal_id = match self.0.signal_id {
+            Some(signal_id) => signal_id,
+            None => return Ok(None),
+        };
+        let (service, context) = get_service(ctx);
+
+        // Request profile from service.
+        let profile = {
+            let request = GetSignalProfileRequest { signal_id };
+            let response = service
+                .get_signal_profile(context, request)
+                .await
+                .into_field_result()?;
+            response.profile
+        };
+
+        // Return signal object.
+        Ok(profile.map(Into::into))
+    }
+
+    /// The `User` that reported this measurement by hand, unless it was
+    /// reported by a `Signal`.
+    async fn user(&self, ctx: &Context<'_>) -> FieldResult<Option<User>> {
+        let user_id = match self.0.user_id {
+            Some(user_id) => user_id,
+            None => return Ok(None),
+        };
+        let (service, context) = get_service(ctx);
+
+        // Request user from service.
+        let user = {
+            let request = GetUserRequest { user_id };
+            let response = service
+                .get_user(context, request)
+                .await
+                .into_field_result()?;
+            response.user
+        };
+
+        // Return user object.
+        Ok(user.map(Into::into))
+    }
+
+    /// Whether the reading was accepted as-is, or adjusted because it
+    /// exceeded the `Shelter`'s capacity.
+    async fn status(&self) -> ShelterMeasurementStatus {
+        self.0.status.into()
+    }
+
     async fn timestamp(&self) -> &DateTime {
         &self.0.created_at
     }
+
+    /// When an admin retracted the measurement, after which it no longer
+    /// affects the `Shelter`'s occupancy.
+    async fn retracted_at(&self) -> Option<&DateTime> {
+        self.0.retracted_at.as_ref()
+    }
+
+    /// The admin who retracted the measurement.
+    async fn retracted_by(
+        &self,
+        ctx: &Context<'_>,
+    ) -> FieldResult<Option<User>> {
+        let user_id = match self.0.retracted_by_id {
+            Some(user_id) => user_id,
+            None => return Ok(None),
+        };
+        let (service, context) = get_service(ctx);
+
+        // Request user from service.
+        let user = {
+            let request = GetUserRequest { user_id };
+            let response = service
+                .get_user(context, request)
+                .await
+                .into_field_result()?;
+            response.user
+        };
+
+        // Return user object.
+        Ok(user.map(Into::into))
+    }
+
+    /// Why the measurement was retracted.
+    async fn retraction_reason(&self) -> Option<&str> {
+        self.0.retraction_reason.as_deref()
+    }
+
+    /// The retracted `ShelterMeasurement` that this measurement replaces.
+    async fn correction_of(
+        &self,
+        ctx: &Context<'_>,
+    ) -> FieldResult<Option<ShelterMeasurement>> {
+        let measurement_id = match self.0.correction_of_id {
+            Some(measurement_id) => measurement_id,
+            None => return Ok(None),
+        };
+        let (service, context) = get_service(ctx);
+
+        // Request measurement from service.
+        let measurement = {
+            let request = GetShelterMeasurementRequest { measurement_id };
+            let response = service
+                .get_shelter_measurement(context, request)
+                .await
+                .into_field_result()?;
+            response.measurement
+        };
+
+        // Return measurement object.
+        Ok(measurement.map(Into::into))
+    }
+}
+
+/// How a `Signal`'s reading was validated against its `Shelter`'s capacity.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Enum)]
+pub enum ShelterMeasurementStatus {
+    /// The reading was within the `Shelter`'s capacity.
+    Accepted,
+
+    /// The reading, or the occupancy it was aggregated into, was outside the
+    /// `Shelter`'s capacity, and was clamped to it.
+    Clamped,
+
+    /// The reading exceeded the `Shelter`'s capacity, and doesn't affect its
+    /// occupancy.
+    Flagged,
+}
+
+impl From<ShelterMeasurementStatusRepr> for ShelterMeasurementStatus {
+    fn from(status: ShelterMeasurementStatusRepr) -> Self {
+        use ShelterMeasurementStatus::*;
+        use ShelterMeasurementStatusRepr as Repr;
+        match status {
+            Repr::Accepted => Accepted,
+            Repr::Clamped => Clamped,
+            Repr::Flagged => Flagged,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Hash)]
@@ -70,3 +216,152 @@ impl ShelterMeasurementQueries {
         Ok(measurement.map(Into::into))
     }
 }
+
+#[derive(Debug, Clone, Hash)]
+pub struct ShelterMeasurementMutations;
+
+#[derive(Debug, Clone, InputObject)]
+pub struct RetractShelterMeasurementInput {
+    pub measurement_id: Id,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct RetractShelterMeasurementPayload {
+    pub shelter: Shelter,
+    pub measurement: ShelterMeasurement,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CorrectShelterMeasurementInput {
+    pub measurement_id: Id,
+    pub reason: String,
+
+    /// The corrected reading, for a measurement reported by a `Signal`.
+    pub value: Option<u16>,
+
+    /// The corrected occupancy, for a measurement reported by a `User`.
+    pub occupancy: Option<ShelterSpaceInput>,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct CorrectShelterMeasurementPayload {
+    pub shelter: Shelter,
+
+    /// The `ShelterMeasurement` that replaces the retracted one.
+    pub measurement: ShelterMeasurement,
+}
+
+#[Object]
+impl ShelterMeasurementMutations {
+    /// Retract a wrong `ShelterMeasurement`, and recompute its `Shelter`'s
+    /// occupancy without it.
+    ///
+    /// Later delta measurements are reapplied without it.
+    async fn retract_shelter_measurement(
+        &self,
+        ctx: &Context<'_>,
+        input: RetractShelterMeasurementInput,
+    ) -> FieldResult<RetractShelterMeasurementPayload> {
+        let RetractShelterMeasurementInput {
+            measurement_id,
+            reason,
+        } = input;
+
+        // Validate input.
+        let measurement_id = measurement_id
+            .get::<ShelterMeasurement>()
+            .context("invalid shelter measurement ID")
+            .into_field_result()?;
+        let reason = reason
+            .try_into()
+            .context("invalid reason")
+            .into_field_result()?;
+
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Retract measurement.
+        let (shelter, measurement) = {
+            let request = RetractShelterMeasurementRequest {
+                measurement_id,
+                reason,
+            };
+            let response = service
+                .retract_shelter_measurement(context, request)
+                .await
+                .into_field_result()?;
+            (response.shelter, response.measurement)
+        };
+
+        // Respond with payload.
+        let payload = RetractShelterMeasurementPayload {
+            shelter: shelter.into(),
+            measurement: measurement.into(),
+        };
+        Ok(payload)
+    }
+
+    /// Replace a wrong `ShelterMeasurement` with a corrected one, and
+    /// recompute its `Shelter`'s occupancy.
+    ///
+    /// Later delta measurements are reapplied on top of the correction.
+    async fn correct_shelter_measurement(
+        &self,
+        ctx: &Context<'_>,
+        input: CorrectShelterMeasurementInput,
+    ) -> FieldResult<CorrectShelterMeasurementPayload> {
+        let CorrectShelterMeasurementInput {
+            measurement_id,
+            reason,
+            value,
+            occupancy,
+        } = input;
+
+        // Validate input.
+        let measurement_id = measurement_id
+            .get::<ShelterMeasurement>()
+            .context("invalid shelter measurement ID")
+            .into_field_result()?;
+        let reason = reason
+            .try_into()
+            .context("invalid reason")
+            .into_field_result()?;
+        let correction = match (value, occupancy) {
+            (Some(value), None) => ShelterMeasurementCorrection::Value(value),
+            (None, Some(occupancy)) => {
+                ShelterMeasurementCorrection::Occupancy(occupancy.into())
+            }
+            _ => {
+                let error = FieldError::new(
+                    "must correct either value or occupancy, but not both",
+                );
+                return Err(error);
+            }
+        };
+
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Correct measurement.
+        let (shelter, measurement) = {
+            let request = CorrectShelterMeasurementRequest {
+                measurement_id,
+                reason,
+                correction,
+            };
+            let response = service
+                .correct_shelter_measurement(context, request)
+                .await
+                .into_field_result()?;
+            (response.shelter, response.measurement)
+        };
+
+        // Respond with payload.
+        let payload = CorrectShelterMeasurementPayload {
+            shelter: shelter.into(),
+            measurement: measurement.into(),
+        };
+        Ok(payload)
+    }
+}
@@ -0,0 +1,224 @@
+use super::prelude::*;
+
+use service::ShelterMembership as ShelterMembershipRepr;
+use service::ShelterRole as ShelterRoleRepr;
+
+use service::GetUserRequest;
+use service::GrantShelterMembershipRequest;
+use service::RevokeShelterMembershipRequest;
+
+#[derive(Debug, Clone, Hash)]
+pub struct ShelterMembership(ShelterMembershipRepr);
+
+impl From<ShelterMembershipRepr> for ShelterMembership {
+    fn from(membership: ShelterMembershipRepr) -> Self {
+        Self(membership)
+    }
+}
+
+/// A `ShelterMembership` grants a `User` a role within a `Shelter`.
+#[Object]
+impl ShelterMembership {
+    async fn id(&self) -> Id {
+        Id::new::<Self>(self.0.id)
+    }
+
+    async fn created_at(&self) -> &DateTime {
+        &self.0.created_at
+    }
+
+    async fn updated_at(&self) -> &DateTime {
+        &self.0.updated_at
+    }
+
+    async fn shelter(&self, ctx: &Context<'_>) -> FieldResult<Shelter> {
+        let loaders = get_loaders(ctx);
+
+        // Load shelter.
+        let shelter = loaders
+            .shelters
+            .load_one(self.0.shelter_id)
+            .await?
+            .context("shelter not found")?;
+
+        // Return shelter object.
+        Ok(shelter.into())
+    }
+
+    async fn user(&self, ctx: &Context<'_>) -> FieldResult<User> {
+        let (service, context) = get_service(ctx);
+
+        // Request user from service.
+        let user = {
+            let request = GetUserRequest {
+                user_id: self.0.user_id,
+            };
+            let response = service
+                .get_user(context, request)
+                .await
+                .into_field_result()?;
+            response.user.context("user not found")?
+        };
+
+        // Return user object.
+        Ok(user.into())
+    }
+
+    async fn role(&self) -> ShelterRole {
+        self.0.role.into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Enum)]
+pub enum ShelterRole {
+    Viewer,
+    Staff,
+    Manager,
+    Owner,
+}
+
+impl From<ShelterRole> for ShelterRoleRepr {
+    fn from(role: ShelterRole) -> Self {
+        use ShelterRole::*;
+        use ShelterRoleRepr as Repr;
+        match role {
+            Viewer => Repr::Viewer,
+            Staff => Repr::Staff,
+            Manager => Repr::Manager,
+            Owner => Repr::Owner,
+        }
+    }
+}
+
+impl From<ShelterRoleRepr> for ShelterRole {
+    fn from(role: ShelterRoleRepr) -> Self {
+        use ShelterRole::*;
+        use ShelterRoleRepr as Repr;
+        match role {
+            Repr::Viewer => Viewer,
+            Repr::Staff => Staff,
+            Repr::Manager => Manager,
+            Repr::Owner => Owner,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct ShelterMembershipMutations;
+
+#[derive(Debug, Clone, InputObject)]
+pub struct GrantShelterMembershipInput {
+    pub shelter_id: Id,
+    pub user_id: Id,
+    pub role: ShelterRole,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct GrantShelterMembershipPayload {
+    pub membership: ShelterMembership,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct RevokeShelterMembershipInput {
+    pub shelter_id: Id,
+    pub user_id: Id,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct RevokeShelterMembershipPayload {
+    pub membership: ShelterMembership,
+}
+
+#[Object]
+impl ShelterMembershipMutations {
+    /// Grant a `User` a role within a `Shelter`, replacing any role they
+    /// already have.
+    async fn grant_shelter_membership(
+        &self,
+        ctx: &Context<'_>,
+        input: GrantShelterMembershipInput,
+    ) -> FieldResult<GrantShelterMembershipPayload> {
+        let GrantShelterMembershipInput {
+            shelter_id,
+            user_id,
+            role,
+        } = input;
+
+        // Decode IDs.
+        let shelter_id = shelter_id
+            .get::<Shelter>()
+            .context("invalid shelter ID")
+            .into_field_result()?;
+        let user_id = user_id
+            .get::<User>()
+            .context("invalid user ID")
+            .into_field_result()?;
+
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Grant membership in service.
+        let membership = {
+            let request = GrantShelterMembershipRequest {
+                shelter_id,
+                user_id,
+                role: role.into(),
+            };
+            let response = service
+                .grant_shelter_membership(context, request)
+                .await
+                .into_field_result()?;
+            response.membership
+        };
+
+        // Respond with payload.
+        let payload = GrantShelterMembershipPayload {
+            membership: membership.into(),
+        };
+        Ok(payload)
+    }
+
+    /// Revoke a `User`'s role within a `Shelter`.
+    async fn revoke_shelter_membership(
+        &self,
+        ctx: &Context<'_>,
+        input: RevokeShelterMembershipInput,
+    ) -> FieldResult<RevokeShelterMembershipPayload> {
+        let RevokeShelterMembershipInput {
+            shelter_id,
+            user_id,
+        } = input;
+
+        // Decode IDs.
+        let shelter_id = shelter_id
+            .get::<Shelter>()
+            .context("invalid shelter ID")
+            .into_field_result()?;
+        let user_id = user_id
+            .get::<User>()
+            .context("invalid user ID")
+            .into_field_result()?;
+
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Revoke membership in service.
+        let membership = {
+            let request = RevokeShelterMembershipRequest {
+                shelter_id,
+                user_id,
+            };
+            let response = service
+                .revoke_shelter_membership(context, request)
+                .await
+                .into_field_result()?;
+            response.membership
+        };
+
+        // Respond with payload.
+        let payload = RevokeShelterMembershipPayload {
+            membership: membership.into(),
+        };
+        Ok(payload)
+    }
+}
@@ -2,19 +2,30 @@ use super::prelude::*;
 
 use service::Slug;
 
+use service::ShelterMeasurementFilter;
+
+use service::RejectedSignalObservation as RejectedSignalObservationRepr;
 use service::ShelterMeasure as ShelterMeasureRepr;
 use service::Signal as SignalRepr;
+use service::SignalMeasurement;
+use service::SignalObservation;
 use service::SignalProfile;
 
+use service::CountSignalMeasurementsRequest;
+use service::CountSignalProfilesRequest;
 use service::CreateSignalMeasurementRequest;
+use service::CreateSignalMeasurementsRequest;
 use service::CreateSignalRequest;
 use service::DeleteSignalRequest;
-use service::GetShelterRequest;
 use service::GetSignalProfileBySlugRequest;
 use service::GetSignalProfileRequest;
-use service::GetSignalSecretRequest;
 use service::ListSignalMeasurementsRequest;
 use service::ListSignalProfilesRequest;
+use service::PingSignalRequest;
+use service::RotateSignalSecretRequest;
+use service::VerifySignalSecretRequest;
+
+use service::Principal;
 
 #[derive(Debug, Clone, From, Hash)]
 pub struct Signal(SignalProfile);
@@ -28,7 +39,7 @@ impl From<SignalRepr> for Signal {
 /// A `Signal` is a device that reports `Shelter` occupancy measurements.
 #[Object]
 impl Signal {
-    async fn id(&self) -> Id {
+    pub async fn id(&self) -> Id {
         Id::new::<Self>(self.0.id)
     }
 
@@ -41,20 +52,14 @@ impl Signal {
     }
 
     async fn shelter(&self, ctx: &Context<'_>) -> FieldResult<Shelter> {
-        let (service, context) = get_service(ctx);
+        let loaders = get_loaders(ctx);
 
-        // Request shelter from service.
-        let shelter = {
-            let context = context.internal();
-            let request = GetShelterRequest {
-                shelter_id: self.0.shelter_id,
-            };
-            let response = service
-                .get_shelter(&context, request)
-                .await
-                .into_field_result()?;
-            response.shelter.context("shelter not found")?
-        };
+        // Load shelter.
+        let shelter = loaders
+            .shelters
+            .load_one(self.0.shelter_id)
+            .await?
+            .context("shelter not found")?;
 
         // Return shelter object.
         Ok(shelter.into())
@@ -65,93 +70,133 @@ impl Signal {
         measure.into()
     }
 
-    async fn value(&self, ctx: &Context<'_>) -> FieldResult<Option<u16>> {
-        let (service, context) = get_service(ctx);
-
-        let measurements = {
-            let request = ListSignalMeasurementsRequest {
-                signal_id: self.0.id,
-                limit: 1,
-                offset: 0,
-            };
-            let response = service
-                .list_signal_measurements(context, request)
-                .await
-                .into_field_result()?;
-            response.measurements
-        };
-
-        let occupancy =
-            if let Some(measurement) = measurements.into_iter().next() {
-                measurement.occupancy
-            } else {
-                return Ok(None);
-            };
+    /// When the `Signal` was archived, after which it no longer reports
+    /// measurements.
+    async fn archived_at(&self) -> Option<&DateTime> {
+        self.0.archived_at.as_ref()
+    }
 
-        use ShelterMeasureRepr::*;
-        let value = match self.0.measure {
-            Spots => occupancy.spots,
-            Beds => occupancy.beds,
-        };
-        Ok(Some(value))
+    /// When the `Signal` was disabled for repeatedly exceeding its rate
+    /// limit, after which it can't report measurements until its secret is
+    /// rotated.
+    async fn disabled_at(&self) -> Option<&DateTime> {
+        self.0.disabled_at.as_ref()
     }
 
-    async fn secret(&self, ctx: &Context<'_>) -> FieldResult<String> {
-        let (service, context) = get_service(ctx);
+    /// When the `Signal` last reported a measurement or heartbeat.
+    async fn last_seen_at(&self) -> Option<&DateTime> {
+        self.0.last_seen_at.as_ref()
+    }
 
-        let secret = {
-            let request = GetSignalSecretRequest {
-                signal_id: self.0.id,
-            };
-            let response = service
-                .get_signal_secret(context, request)
-                .await
-                .into_field_result()?;
-            response.secret
-        };
+    /// Whether the `Signal` has gone quiet for long enough that its readings
+    /// may no longer be reliable.
+    async fn is_stale(&self, ctx: &Context<'_>) -> bool {
+        let (service, _) = get_service(ctx);
+        service.is_signal_stale(self.0.last_seen_at.as_ref())
+    }
 
-        Ok(secret)
+    async fn value(&self, ctx: &Context<'_>) -> FieldResult<Option<u16>> {
+        let loaders = get_loaders(ctx);
+
+        // Load latest measurement.
+        let measurement = loaders
+            .latest_signal_measurements
+            .load_one(self.0.id)
+            .await?;
+        let value = measurement.and_then(|measurement| measurement.value);
+        Ok(value)
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn measurements(
         &self,
         ctx: &Context<'_>,
 
-        // TODO: Use `default` instead of `default_with` once
-        // https://github.com/async-graphql/async-graphql/issues/361
-        // is resolved.
+        #[rustfmt::skip]
+        #[graphql(desc = "Only list `ShelterMeasurement`s after this cursor.")]
+        after: Option<Cursor>,
+
         #[rustfmt::skip]
         #[graphql(
-            desc = "The maximum number of `ShelterMeasurement`s to fetch.",
-            default_with = "25"
+            desc = "Only list `ShelterMeasurement`s before this cursor."
         )]
-        limit: u32,
+        before: Option<Cursor>,
 
         #[rustfmt::skip]
         #[graphql(
-            desc = "The number of initial `ShelterMeasurement`s to skip.",
-            default
+            desc = "The number of leading `ShelterMeasurement`s to fetch."
         )]
-        offset: u32,
-    ) -> FieldResult<Vec<ShelterMeasurement>> {
+        first: Option<u32>,
+
+        #[rustfmt::skip]
+        #[graphql(
+            desc = "The number of trailing `ShelterMeasurement`s to fetch."
+        )]
+        last: Option<u32>,
+
+        #[rustfmt::skip]
+        #[graphql(
+            desc = "Only list `ShelterMeasurement`s taken at or after this time."
+        )]
+        since: Option<DateTime>,
+
+        #[rustfmt::skip]
+        #[graphql(
+            desc = "Only list `ShelterMeasurement`s taken before this time."
+        )]
+        until: Option<DateTime>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "Whether to list retracted `ShelterMeasurement`s.")]
+        include_retracted: Option<bool>,
+    ) -> FieldResult<Connection<ShelterMeasurement>> {
+        let filter = ShelterMeasurementFilter {
+            since,
+            until,
+            include_retracted: include_retracted.unwrap_or_default(),
+        };
+        let window = KeysetWindow::new(after, before, first, last)
+            .into_field_result()?;
+
         let (service, context) = get_service(ctx);
+        let context = context.internal();
+
+        // Count measurements, which requires a scan of the matching history.
+        let total = if ctx.look_ahead().field("totalCount").exists() {
+            let request = CountSignalMeasurementsRequest {
+                signal_id: self.0.id,
+                filter: filter.clone(),
+            };
+            let response = service
+                .count_signal_measurements(&context, request)
+                .await
+                .into_field_result()?;
+            response.count
+        } else {
+            0
+        };
 
-        let measurements = {
-            let context = context.internal();
+        let (measurements, has_more) = {
             let request = ListSignalMeasurementsRequest {
                 signal_id: self.0.id,
-                limit,
-                offset,
+                filter,
+                after: window.after.map(Into::into),
+                before: window.before.map(Into::into),
+                limit: window.limit,
+                from_end: window.from_end,
             };
             let response = service
                 .list_signal_measurements(&context, request)
                 .await
                 .into_field_result()?;
-            response.measurements
+            (response.measurements, response.has_more)
         };
 
-        let measurements = measurements.into_iter().map(Into::into).collect();
-        Ok(measurements)
+        let connection =
+            window.connect(measurements, has_more, total, |measurement| {
+                (measurement.created_at, measurement.id)
+            });
+        Ok(connection)
     }
 }
 
@@ -251,25 +296,50 @@ impl SignalQueries {
         &self,
         ctx: &Context<'_>,
 
-        // TODO: Use `default` instead of `default_with` once
-        // https://github.com/async-graphql/async-graphql/issues/361
-        // is resolved.
         #[rustfmt::skip]
-        #[graphql(
-            desc = "The maximum number of `Signal`s to fetch.",
-            default_with = "25"
-        )]
-        limit: u32,
+        #[graphql(desc = "Only list `Signal`s after this cursor.")]
+        after: Option<Cursor>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "Only list `Signal`s before this cursor.")]
+        before: Option<Cursor>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "The number of leading `Signal`s to fetch.")]
+        first: Option<u32>,
 
         #[rustfmt::skip]
-        #[graphql(desc = "The number of initial `Signal`s to skip.", default)]
-        offset: u32,
-    ) -> FieldResult<Vec<Signal>> {
+        #[graphql(desc = "The number of trailing `Signal`s to fetch.")]
+        last: Option<u32>,
+
+        #[rustfmt::skip]
+        #[graphql(desc = "Whether to include archived `Signal`s.")]
+        include_archived: Option<bool>,
+    ) -> FieldResult<Connection<Signal>> {
+        let include_archived = include_archived.unwrap_or_default();
         let (service, context) = get_service(ctx);
 
+        // Count signals.
+        let total = {
+            let request = CountSignalProfilesRequest { include_archived };
+            let response = service
+                .count_signal_profiles(context, request)
+                .await
+                .into_field_result()?;
+            response.count
+        };
+
+        // Determine which signals to fetch.
+        let window = ConnectionWindow::new(after, before, first, last, total)
+            .into_field_result()?;
+
         // Request signals from service.
         let profiles = {
-            let request = ListSignalProfilesRequest { limit, offset };
+            let request = ListSignalProfilesRequest {
+                limit: window.limit(),
+                offset: window.offset(),
+                include_archived,
+            };
             let response = service
                 .list_signal_profiles(context, request)
                 .await
@@ -277,8 +347,7 @@ impl SignalQueries {
             response.profiles
         };
 
-        let profiles = profiles.into_iter().map(Into::into).collect();
-        Ok(profiles)
+        Ok(window.connect(profiles))
     }
 }
 
@@ -295,13 +364,27 @@ pub struct CreateSignalInput {
 #[derive(Debug, Clone, SimpleObject)]
 pub struct CreateSignalPayload {
     pub signal: Signal,
+
+    /// The `Signal`'s secret, which is only shown once.
+    pub secret: String,
 }
 
 #[derive(Debug, Clone, Hash, InputObject)]
 pub struct CreateSignalMeasurementInput {
     pub signal_id: Id,
     pub signal_secret: String,
-    pub measurement: u16,
+
+    /// A count that replaces the `Shelter`'s current occupancy.
+    pub measurement: Option<u16>,
+
+    /// A change to the `Shelter`'s current occupancy, such as `1` when
+    /// someone enters and `-1` when they leave. The resulting occupancy is
+    /// validated against the `Shelter`'s capacity like other measurements.
+    pub delta: Option<i32>,
+
+    /// A key that identifies this report, so that retrying it returns the
+    /// original measurement rather than creating another.
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Clone, Hash, SimpleObject)]
@@ -309,6 +392,86 @@ pub struct CreateSignalMeasurementPayload {
     pub measurement: ShelterMeasurement,
 }
 
+#[derive(Debug, Clone, Hash, InputObject)]
+pub struct SignalObservationInput {
+    pub observed_at: DateTime,
+    pub measurement: u16,
+}
+
+#[derive(Debug, Clone, Hash, InputObject)]
+pub struct CreateSignalMeasurementsInput {
+    pub signal_id: Id,
+    pub signal_secret: String,
+    pub observations: Vec<SignalObservationInput>,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct CreateSignalMeasurementsPayload {
+    pub shelter: Shelter,
+
+    /// The created measurements, from oldest to newest.
+    pub measurements: Vec<ShelterMeasurement>,
+
+    /// The observations that weren't recorded, because they exceeded the
+    /// `Shelter`'s capacity and it rejects such measurements.
+    pub rejected: Vec<RejectedSignalObservation>,
+}
+
+#[derive(Debug, Clone, Hash, SimpleObject)]
+pub struct RejectedSignalObservation {
+    pub observed_at: DateTime,
+    pub measurement: u16,
+
+    /// Why the observation wasn't recorded.
+    pub reason: String,
+}
+
+impl From<RejectedSignalObservationRepr> for RejectedSignalObservation {
+    fn from(rejected: RejectedSignalObservationRepr) -> Self {
+        let RejectedSignalObservationRepr {
+            observation,
+            reason,
+        } = rejected;
+        let SignalObservation {
+            observed_at,
+            measurement,
+        } = observation;
+        Self {
+            observed_at,
+            measurement,
+            reason,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, InputObject)]
+pub struct PingSignalInput {
+    pub signal_id: Id,
+    pub signal_secret: String,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct PingSignalPayload {
+    pub signal: Signal,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct RotateSignalSecretInput {
+    pub signal_id: Id,
+
+    /// The number of seconds for which the current secret remains valid, so
+    /// that devices can be re-provisioned without dropping measurements.
+    pub grace_period_seconds: Option<u32>,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct RotateSignalSecretPayload {
+    pub signal: Signal,
+
+    /// The `Signal`'s new secret, which is only shown once.
+    pub secret: String,
+}
+
 #[derive(Debug, Clone, InputObject)]
 pub struct DeleteSignalInput {
     pub signal_id: Id,
@@ -343,7 +506,7 @@ impl SignalMutations {
         let (service, context) = get_service(ctx);
 
         // Create signal in service.
-        let signal = {
+        let (signal, secret) = {
             let request = {
                 let name = name
                     .try_into()
@@ -360,16 +523,19 @@ impl SignalMutations {
                 .create_signal(context, request)
                 .await
                 .into_field_result()?;
-            response.signal
+            (response.signal, response.secret)
         };
 
         // Respond with payload.
         let payload = CreateSignalPayload {
             signal: signal.into(),
+            secret,
         };
         Ok(payload)
     }
 
+    /// Record a measurement reported by a `Signal`, which is either an
+    /// absolute count or a change to the current count.
     async fn create_signal_measurement(
         &self,
         ctx: &Context<'_>,
@@ -379,6 +545,8 @@ impl SignalMutations {
             signal_id,
             signal_secret,
             measurement,
+            delta,
+            idempotency_key,
         } = input;
 
         // Parse signal ID.
@@ -387,18 +555,34 @@ impl SignalMutations {
             .context("invalid signal ID")
             .into_field_result()?;
 
+        // Parse measurement.
+        let measurement = match (measurement, delta) {
+            (Some(value), None) => SignalMeasurement::Absolute(value),
+            (None, Some(delta)) => SignalMeasurement::Delta(delta),
+            _ => {
+                let error = FieldError::new(
+                    "exactly one of measurement and delta is required",
+                );
+                return Err(error);
+            }
+        };
+
         // Get service.
         let (service, context) = get_service(ctx);
 
+        // Authenticate as signal.
+        let context =
+            authenticate(service, context, signal_id, signal_secret).await?;
+
         // Create measurement.
         let measurement = {
             let request = CreateSignalMeasurementRequest {
                 signal_id,
-                signal_secret,
                 measurement,
+                idempotency_key,
             };
             let response = service
-                .create_signal_measurement(context, request)
+                .create_signal_measurement(&context, request)
                 .await
                 .context("failed to create measurement")
                 .into_field_result()?;
@@ -411,7 +595,157 @@ impl SignalMutations {
         };
         Ok(payload)
     }
-    /// Delete a `Signal`.
+    /// Record a batch of measurements observed by a `Signal`, such as those
+    /// it made while offline. The `Shelter`'s occupancy is only updated if
+    /// the newest observation is newer than its current state.
+    async fn create_signal_measurements(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateSignalMeasurementsInput,
+    ) -> FieldResult<CreateSignalMeasurementsPayload> {
+        let CreateSignalMeasurementsInput {
+            signal_id,
+            signal_secret,
+            observations,
+        } = input;
+
+        // Parse signal ID.
+        let signal_id = signal_id
+            .get::<Signal>()
+            .context("invalid signal ID")
+            .into_field_result()?;
+
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Authenticate as signal.
+        let context =
+            authenticate(service, context, signal_id, signal_secret).await?;
+
+        // Create measurements.
+        let (shelter, measurements, rejected) = {
+            let observations = observations
+                .into_iter()
+                .map(|observation| {
+                    let SignalObservationInput {
+                        observed_at,
+                        measurement,
+                    } = observation;
+                    SignalObservation {
+                        observed_at,
+                        measurement,
+                    }
+                })
+                .collect();
+            let request = CreateSignalMeasurementsRequest {
+                signal_id,
+                observations,
+            };
+            let response = service
+                .create_signal_measurements(&context, request)
+                .await
+                .context("failed to create measurements")
+                .into_field_result()?;
+            (response.shelter, response.measurements, response.rejected)
+        };
+
+        // Respond with payload.
+        let payload = CreateSignalMeasurementsPayload {
+            shelter: shelter.into(),
+            measurements: measurements.into_iter().map(Into::into).collect(),
+            rejected: rejected.into_iter().map(Into::into).collect(),
+        };
+        Ok(payload)
+    }
+
+    /// Record a heartbeat from a `Signal`, which reports that it is still
+    /// online without measuring anything.
+    async fn ping_signal(
+        &self,
+        ctx: &Context<'_>,
+        input: PingSignalInput,
+    ) -> FieldResult<PingSignalPayload> {
+        let PingSignalInput {
+            signal_id,
+            signal_secret,
+        } = input;
+
+        // Parse signal ID.
+        let signal_id = signal_id
+            .get::<Signal>()
+            .context("invalid signal ID")
+            .into_field_result()?;
+
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Authenticate as signal.
+        let context =
+            authenticate(service, context, signal_id, signal_secret).await?;
+
+        // Record heartbeat.
+        let signal = {
+            let request = PingSignalRequest { signal_id };
+            let response = service
+                .ping_signal(&context, request)
+                .await
+                .context("failed to record heartbeat")
+                .into_field_result()?;
+            response.signal
+        };
+
+        // Respond with payload.
+        let payload = PingSignalPayload {
+            signal: signal.into(),
+        };
+        Ok(payload)
+    }
+
+    /// Replace a `Signal`'s secret, re-enabling it if it was disabled.
+    async fn rotate_signal_secret(
+        &self,
+        ctx: &Context<'_>,
+        input: RotateSignalSecretInput,
+    ) -> FieldResult<RotateSignalSecretPayload> {
+        let RotateSignalSecretInput {
+            signal_id,
+            grace_period_seconds,
+        } = input;
+
+        // Validate signal ID.
+        let signal_id = signal_id
+            .get::<Signal>()
+            .context("invalid signal ID")
+            .into_field_result()?;
+
+        // Get service.
+        let (service, context) = get_service(ctx);
+
+        // Rotate secret in service.
+        let (signal, secret) = {
+            let grace_period = grace_period_seconds
+                .map(|seconds| Duration::from_secs(seconds.into()));
+            let request = RotateSignalSecretRequest {
+                signal_id,
+                grace_period,
+            };
+            let response = service
+                .rotate_signal_secret(context, request)
+                .await
+                .into_field_result()?;
+            (response.signal, response.secret)
+        };
+
+        // Respond with payload.
+        let payload = RotateSignalSecretPayload {
+            signal: signal.into(),
+            secret,
+        };
+        Ok(payload)
+    }
+
+    /// Delete a `Signal`, or archive it if it has already reported
+    /// measurements.
     async fn delete_signal(
         &self,
         ctx: &Context<'_>,
@@ -445,3 +779,31 @@ impl SignalMutations {
         Ok(payload)
     }
 }
+
+/// Authenticate as a signal, using its secret.
+async fn authenticate(
+    service: &Service,
+    context: &ServiceContext,
+    signal_id: Uuid,
+    secret: String,
+) -> FieldResult<ServiceContext> {
+    let remote_addr = context.remote_addr;
+    let context = ServiceContext::anonymous().with_remote_addr(remote_addr);
+    let request = VerifySignalSecretRequest { signal_id, secret };
+    let response = service
+        .verify_signal_secret(&context, request)
+        .await
+        .into_field_result()?;
+    if !response.is_valid {
+        return Err(FieldError::new("not authorized"));
+    }
+    if response.is_archived {
+        return Err(FieldError::new("signal is archived"));
+    }
+    if response.is_disabled {
+        return Err(FieldError::new("signal is disabled"));
+    }
+    let context = ServiceContext::new(Principal::Signal(signal_id))
+        .with_remote_addr(remote_addr);
+    Ok(context)
+}
@@ -0,0 +1,16 @@
+use super::prelude::*;
+
+#[derive(Debug, Clone, MergedSubscription)]
+pub struct Subscription(ShelterSubscriptions);
+
+impl Subscription {
+    pub fn new() -> Self {
+        Self(ShelterSubscriptions)
+    }
+}
+
+impl Default for Subscription {
+    fn default() -> Self {
+        Self::new()
+    }
+}
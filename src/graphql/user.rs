@@ -22,7 +22,7 @@ impl From<UserRepr> for User {
 /// A `User` owns a Chalmers Project account.
 #[Object]
 impl User {
-    async fn id(&self) -> Id {
+    pub async fn id(&self) -> Id {
         Id::new::<Self>(self.0.id)
     }
 
@@ -95,7 +95,7 @@ impl UserQueries {
             Some(auth) => auth,
             None => return Ok(None),
         };
-        let firebase_id = auth.claims().user_id.to_owned();
+        let firebase_id = auth.claims().account_id().to_owned();
 
         // Request viewer from service.
         let viewer = {
@@ -225,17 +225,18 @@ impl UserMutations {
         } = input;
 
         // Get auth claims.
-        let AuthClaims {
-            user_id: firebase_id,
-            email,
-            email_verified,
-            ..
-        } = {
+        let claims = {
             let auth = get_auth(ctx)
                 .context("not authenticated")
                 .into_field_result()?;
             auth.claims()
         };
+        let AuthClaims {
+            email,
+            email_verified,
+            ..
+        } = claims;
+        let firebase_id = claims.account_id();
 
         // Get service.
         let (service, context) = get_service(ctx);
@@ -253,14 +254,13 @@ impl UserMutations {
                     .transpose()
                     .context("invalid website URL")
                     .into_field_result()?;
-                let email = {
-                    let email = email
-                        .parse()
-                        .context("invalid email address")
-                        .into_field_result()?;
-                    let email = Verifiable::new(email, *email_verified);
-                    Some(email)
-                };
+                let email = email
+                    .as_ref()
+                    .map(|email| email.parse())
+                    .transpose()
+                    .context("invalid email address")
+                    .into_field_result()?
+                    .map(|email| Verifiable::new(email, *email_verified));
 
                 CreateUserRequest {
                     firebase_id: firebase_id.to_owned(),
@@ -351,14 +351,13 @@ impl UserMutations {
                     .transpose()
                     .context("invalid image URL")
                     .into_field_result()?;
-                let email = {
-                    let email = email
-                        .parse()
-                        .context("invalid email")
-                        .into_field_result()?;
-                    let email = Verifiable::new(email, *email_verified);
-                    Some(email)
-                };
+                let email = email
+                    .as_ref()
+                    .map(|email| email.parse())
+                    .transpose()
+                    .context("invalid email")
+                    .into_field_result()?
+                    .map(|email| Verifiable::new(email, *email_verified));
 
                 UpdateUserRequest {
                     user_id,
@@ -17,12 +17,18 @@ mod prelude {
     pub use std::io::prelude::*;
 }
 
+pub mod measurement_anomaly;
+pub use measurement_anomaly::*;
+
 pub mod shelter;
 pub use shelter::*;
 
 pub mod shelter_measurement;
 pub use shelter_measurement::*;
 
+pub mod shelter_membership;
+pub use shelter_membership::*;
+
 pub mod signal;
 pub use signal::*;
 
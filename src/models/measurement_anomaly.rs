@@ -0,0 +1,93 @@
+use super::prelude::*;
+
+use service::MeasurementAnomaly as MeasurementAnomalyRepr;
+
+#[derive(
+    Debug, Clone, Serialize, Deserialize, Queryable, Insertable, AsChangeset,
+)]
+#[table_name = "measurement_anomalies"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct MeasurementAnomaly {
+    pub id: Uuid,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+    pub measurement_id: Uuid,
+    pub shelter_id: Uuid,
+    pub signal_id: Uuid,
+    pub kind: String,
+    pub score: f64,
+    pub resolved_at: Option<DateTime>,
+    pub resolved_by_id: Option<Uuid>,
+}
+
+impl From<MeasurementAnomalyRepr> for MeasurementAnomaly {
+    fn from(anomaly: MeasurementAnomalyRepr) -> Self {
+        let MeasurementAnomalyRepr {
+            id,
+            created_at,
+            updated_at,
+
+            measurement_id,
+            shelter_id,
+            signal_id,
+
+            kind,
+            score,
+
+            resolved_at,
+            resolved_by_id,
+        } = anomaly;
+
+        Self {
+            id,
+            created_at,
+            updated_at,
+            measurement_id,
+            shelter_id,
+            signal_id,
+            kind: kind.to_string(),
+            score,
+            resolved_at,
+            resolved_by_id,
+        }
+    }
+}
+
+impl TryFrom<MeasurementAnomaly> for MeasurementAnomalyRepr {
+    type Error = Error;
+
+    fn try_from(anomaly: MeasurementAnomaly) -> Result<Self, Self::Error> {
+        let MeasurementAnomaly {
+            id,
+            created_at,
+            updated_at,
+            measurement_id,
+            shelter_id,
+            signal_id,
+            kind,
+            score,
+            resolved_at,
+            resolved_by_id,
+        } = anomaly;
+
+        let kind = kind.parse().context("failed to parse kind")?;
+
+        let anomaly = MeasurementAnomalyRepr {
+            id,
+            created_at,
+            updated_at,
+
+            measurement_id,
+            shelter_id,
+            signal_id,
+
+            kind,
+            score,
+
+            resolved_at,
+            resolved_by_id,
+        };
+
+        Ok(anomaly)
+    }
+}
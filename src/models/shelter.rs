@@ -1,5 +1,6 @@
 use super::prelude::*;
 
+use service::Coordinate;
 use service::Shelter as ShelterRepr;
 use service::ShelterSpace;
 
@@ -16,7 +17,6 @@ pub type ShelterColumns = (
     shelters::phone,
     shelters::website_url,
     shelters::address,
-    shelters::location,
     shelters::total_spots,
     shelters::total_beds,
     shelters::food,
@@ -24,6 +24,12 @@ pub type ShelterColumns = (
     shelters::image_url,
     shelters::occupied_spots,
     shelters::occupied_beds,
+    shelters::latitude,
+    shelters::longitude,
+    shelters::aggregation,
+    shelters::primary_signal_id,
+    shelters::occupancy_updated_at,
+    shelters::capacity_policy,
 );
 
 pub const SHELTER_COLUMNS: ShelterColumns = (
@@ -37,7 +43,6 @@ pub const SHELTER_COLUMNS: ShelterColumns = (
     shelters::phone,
     shelters::website_url,
     shelters::address,
-    shelters::location,
     shelters::total_spots,
     shelters::total_beds,
     shelters::food,
@@ -45,6 +50,12 @@ pub const SHELTER_COLUMNS: ShelterColumns = (
     shelters::image_url,
     shelters::occupied_spots,
     shelters::occupied_beds,
+    shelters::latitude,
+    shelters::longitude,
+    shelters::aggregation,
+    shelters::primary_signal_id,
+    shelters::occupancy_updated_at,
+    shelters::capacity_policy,
 );
 
 #[derive(
@@ -63,7 +74,6 @@ pub struct Shelter {
     pub phone: String,
     pub website_url: Option<String>,
     pub address: JsonValue,
-    pub location: JsonValue,
     pub total_spots: i32,
     pub total_beds: i32,
     pub food: String,
@@ -71,6 +81,12 @@ pub struct Shelter {
     pub image_url: Option<String>,
     pub occupied_spots: Option<i32>,
     pub occupied_beds: Option<i32>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub aggregation: String,
+    pub primary_signal_id: Option<Uuid>,
+    pub occupancy_updated_at: Option<DateTime>,
+    pub capacity_policy: String,
 }
 
 impl TryFrom<ShelterRepr> for Shelter {
@@ -94,15 +110,19 @@ impl TryFrom<ShelterRepr> for Shelter {
             location,
 
             capacity,
+            capacity_policy,
             occupancy,
+            occupancy_updated_at,
+            aggregation,
+            primary_signal_id,
             food,
             tags,
         } = shelter;
 
         let address =
             to_json_value(address).context("failed to encode address")?;
-        let location =
-            to_json_value(location).context("failed to encode location")?;
+        let Coordinate { x, y } = location;
+        let (longitude, latitude) = (x.into(), y.into());
 
         let ShelterSpace {
             spots: total_spots,
@@ -134,13 +154,18 @@ impl TryFrom<ShelterRepr> for Shelter {
             phone: phone.into(),
             website_url: website_url.map(|url| url.to_string()),
             address,
-            location,
             total_spots: total_spots.into(),
             total_beds: total_beds.into(),
             food: food.to_string(),
             tags: tags.into_iter().map(|tag| tag.to_string()).collect(),
             occupied_spots,
             occupied_beds,
+            latitude,
+            longitude,
+            aggregation: aggregation.to_string(),
+            primary_signal_id,
+            occupancy_updated_at,
+            capacity_policy: capacity_policy.to_string(),
         };
 
         Ok(shelter)
@@ -162,7 +187,6 @@ impl TryFrom<Shelter> for ShelterRepr {
             phone,
             website_url,
             address,
-            location,
             total_spots,
             total_beds,
             food,
@@ -170,6 +194,12 @@ impl TryFrom<Shelter> for ShelterRepr {
             image_url,
             occupied_spots,
             occupied_beds,
+            latitude,
+            longitude,
+            aggregation,
+            primary_signal_id,
+            occupancy_updated_at,
+            capacity_policy,
         } = shelter;
 
         let slug = slug.try_into().context("failed to parse slug")?;
@@ -190,8 +220,10 @@ impl TryFrom<Shelter> for ShelterRepr {
             .context("failed to parse website URL")?;
         let address =
             from_json_value(address).context("failed to decode address")?;
-        let location =
-            from_json_value(location).context("failed to decode location")?;
+        let location = Coordinate {
+            x: longitude as f32,
+            y: latitude as f32,
+        };
 
         let total_spots = total_spots
             .try_into()
@@ -203,6 +235,9 @@ impl TryFrom<Shelter> for ShelterRepr {
             spots: total_spots,
             beds: total_beds,
         };
+        let capacity_policy = capacity_policy
+            .parse()
+            .context("failed to parse capacity policy")?;
 
         let occupied_spots = occupied_spots
             .map(TryInto::try_into)
@@ -217,6 +252,8 @@ impl TryFrom<Shelter> for ShelterRepr {
             _ => None,
         };
 
+        let aggregation =
+            aggregation.parse().context("failed to parse aggregation")?;
         let food = food.parse().context("failed to parse food options")?;
         let tags = tags
             .into_iter()
@@ -242,7 +279,11 @@ impl TryFrom<Shelter> for ShelterRepr {
             location,
 
             capacity,
+            capacity_policy,
             occupancy,
+            occupancy_updated_at,
+            aggregation,
+            primary_signal_id,
             food,
             tags,
         };
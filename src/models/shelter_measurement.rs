@@ -24,7 +24,16 @@ pub struct ShelterMeasurement {
     pub occupied_beds: i32,
     pub total_spots: i32,
     pub total_beds: i32,
-    pub signal_id: Uuid,
+    pub signal_id: Option<Uuid>,
+    pub idempotency_key: Option<String>,
+    pub value: Option<i32>,
+    pub status: String,
+    pub user_id: Option<Uuid>,
+    pub retracted_at: Option<DateTime>,
+    pub retracted_by_id: Option<Uuid>,
+    pub retraction_reason: Option<String>,
+    pub correction_of_id: Option<Uuid>,
+    pub delta: Option<i32>,
 }
 
 impl TryFrom<ShelterMeasurementRepr> for ShelterMeasurement {
@@ -40,9 +49,20 @@ impl TryFrom<ShelterMeasurementRepr> for ShelterMeasurement {
 
             shelter_id,
             signal_id,
+            user_id,
 
             capacity,
             occupancy,
+            value,
+            delta,
+            status,
+
+            retracted_at,
+            retracted_by_id,
+            retraction_reason,
+            correction_of_id,
+
+            idempotency_key,
         } = measurement;
 
         let total_spots = capacity
@@ -73,6 +93,15 @@ impl TryFrom<ShelterMeasurementRepr> for ShelterMeasurement {
             total_spots,
             total_beds,
             signal_id,
+            idempotency_key,
+            value: value.map(Into::into),
+            status: status.to_string(),
+            user_id,
+            retracted_at,
+            retracted_by_id,
+            retraction_reason,
+            correction_of_id,
+            delta,
         };
 
         Ok(measurement)
@@ -93,6 +122,15 @@ impl TryFrom<ShelterMeasurement> for ShelterMeasurementRepr {
             total_beds,
             total_spots,
             signal_id,
+            idempotency_key,
+            value,
+            status,
+            user_id,
+            retracted_at,
+            retracted_by_id,
+            retraction_reason,
+            correction_of_id,
+            delta,
         } = measurement;
 
         let capacity = ShelterSpace {
@@ -120,9 +158,23 @@ impl TryFrom<ShelterMeasurement> for ShelterMeasurementRepr {
 
             shelter_id,
             signal_id,
+            user_id,
 
             capacity,
             occupancy,
+            value: value
+                .map(TryInto::try_into)
+                .transpose()
+                .context("failed to convert value")?,
+            delta,
+            status: status.parse().context("failed to parse status")?,
+
+            retracted_at,
+            retracted_by_id,
+            retraction_reason,
+            correction_of_id,
+
+            idempotency_key,
         };
 
         Ok(measurement)
@@ -0,0 +1,75 @@
+use super::prelude::*;
+
+use service::ShelterMembership as ShelterMembershipRepr;
+
+#[derive(
+    Debug,
+    Clone,
+    Hash,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    AsChangeset,
+)]
+#[table_name = "shelter_memberships"]
+pub struct ShelterMembership {
+    pub id: Uuid,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+    pub shelter_id: Uuid,
+    pub user_id: Uuid,
+    pub role: String,
+}
+
+impl From<ShelterMembershipRepr> for ShelterMembership {
+    fn from(membership: ShelterMembershipRepr) -> Self {
+        let ShelterMembershipRepr {
+            id,
+            created_at,
+            updated_at,
+
+            shelter_id,
+            user_id,
+            role,
+        } = membership;
+
+        Self {
+            id,
+            created_at,
+            updated_at,
+            shelter_id,
+            user_id,
+            role: role.to_string(),
+        }
+    }
+}
+
+impl TryFrom<ShelterMembership> for ShelterMembershipRepr {
+    type Error = Error;
+
+    fn try_from(membership: ShelterMembership) -> Result<Self, Self::Error> {
+        let ShelterMembership {
+            id,
+            created_at,
+            updated_at,
+            shelter_id,
+            user_id,
+            role,
+        } = membership;
+
+        let role = role.parse().context("failed to parse role")?;
+
+        let membership = ShelterMembershipRepr {
+            id,
+            created_at,
+            updated_at,
+
+            shelter_id,
+            user_id,
+            role,
+        };
+
+        Ok(membership)
+    }
+}
@@ -1,5 +1,6 @@
 use super::prelude::*;
 
+use service::PreviousSecret;
 use service::Signal as SignalRepr;
 use service::SignalProfile;
 
@@ -16,7 +17,12 @@ pub struct Signal {
     pub name: String,
     pub shelter_id: Uuid,
     pub measure: String,
-    pub secret: String,
+    pub secret_hash: String,
+    pub previous_secret_hash: Option<String>,
+    pub previous_secret_expires_at: Option<DateTime>,
+    pub archived_at: Option<DateTime>,
+    pub last_seen_at: Option<DateTime>,
+    pub disabled_at: Option<DateTime>,
 }
 
 impl From<SignalRepr> for Signal {
@@ -32,9 +38,22 @@ impl From<SignalRepr> for Signal {
             shelter_id,
             measure,
 
-            secret,
+            secret_hash,
+            previous_secret,
+
+            archived_at,
+            last_seen_at,
+            disabled_at,
         } = signal;
 
+        let (previous_secret_hash, previous_secret_expires_at) =
+            match previous_secret {
+                Some(PreviousSecret { hash, expires_at }) => {
+                    (Some(hash.to_string()), Some(expires_at))
+                }
+                None => (None, None),
+            };
+
         Self {
             id,
             created_at,
@@ -43,7 +62,12 @@ impl From<SignalRepr> for Signal {
             name,
             shelter_id,
             measure: measure.to_string(),
-            secret,
+            secret_hash: secret_hash.to_string(),
+            previous_secret_hash,
+            previous_secret_expires_at,
+            archived_at,
+            last_seen_at,
+            disabled_at,
         }
     }
 }
@@ -60,11 +84,28 @@ impl TryFrom<Signal> for SignalRepr {
             name,
             shelter_id,
             measure,
-            secret,
+            secret_hash,
+            previous_secret_hash,
+            previous_secret_expires_at,
+            archived_at,
+            last_seen_at,
+            disabled_at,
         } = signal;
 
         let slug = slug.try_into().context("failed to parse slug")?;
         let measure = measure.parse().context("failed to parse measure")?;
+        let secret_hash =
+            secret_hash.parse().context("failed to parse secret hash")?;
+        let previous_secret =
+            match (previous_secret_hash, previous_secret_expires_at) {
+                (Some(hash), Some(expires_at)) => {
+                    let hash = hash
+                        .parse()
+                        .context("failed to parse previous secret hash")?;
+                    Some(PreviousSecret { hash, expires_at })
+                }
+                _ => None,
+            };
 
         let signal = SignalRepr {
             id,
@@ -77,7 +118,12 @@ impl TryFrom<Signal> for SignalRepr {
             shelter_id,
             measure,
 
-            secret,
+            secret_hash,
+            previous_secret,
+
+            archived_at,
+            last_seen_at,
+            disabled_at,
         };
 
         Ok(signal)
@@ -96,6 +142,9 @@ impl TryFrom<Signal> for SignalProfile {
             name,
             shelter_id,
             measure,
+            archived_at,
+            last_seen_at,
+            disabled_at,
             ..
         } = signal;
 
@@ -112,6 +161,10 @@ impl TryFrom<Signal> for SignalProfile {
 
             shelter_id,
             measure,
+
+            archived_at,
+            last_seen_at,
+            disabled_at,
         };
 
         Ok(signal)
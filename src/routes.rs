@@ -5,15 +5,20 @@ mod prelude {
 
 use crate::prelude::*;
 
+use warp::addr::remote as remote_socket_addr;
+use warp::header::optional as header;
 use warp::reject::{Reject, Rejection};
 use warp::reply::json as json_reply;
 use warp::reply::{with_status, Reply, Response};
+use warp::Filter;
 
 use http::StatusCode;
 use json::json;
+use std::net::{IpAddr, SocketAddr};
 
 pub mod graphql;
 pub mod healthz;
+pub mod signals;
 
 #[derive(Debug, Clone)]
 pub struct RouteError {
@@ -21,6 +26,15 @@ pub struct RouteError {
     status: StatusCode,
 }
 
+impl RouteError {
+    pub fn new(status: StatusCode, message: &str) -> Self {
+        RouteError {
+            message: message.to_owned(),
+            status,
+        }
+    }
+}
+
 impl Reject for RouteError {}
 
 impl From<Error> for RouteError {
@@ -48,3 +62,26 @@ pub async fn recover(rejection: Rejection) -> Result<impl Reply, Rejection> {
         Err(rejection)
     }
 }
+
+/// Extract the address a request was made from.
+///
+/// If `trust_forwarded_for` is set, the API is assumed to be behind a proxy,
+/// and the last address in the `X-Forwarded-For` header (which is the one
+/// that proxy appended) is used instead of the connecting address.
+pub fn remote_addr(
+    trust_forwarded_for: bool,
+) -> impl Filter<Extract = (Option<IpAddr>,), Error = Rejection> + Clone {
+    remote_socket_addr()
+        .and(header::<String>("X-Forwarded-For"))
+        .map(
+            move |addr: Option<SocketAddr>, forwarded_for: Option<String>| {
+                let forwarded_addr = forwarded_for
+                    .filter(|_| trust_forwarded_for)
+                    .and_then(|forwarded_for| {
+                        let addr = forwarded_for.rsplit(',').next()?;
+                        addr.trim().parse().ok()
+                    });
+                forwarded_addr.or_else(|| addr.map(|addr| addr.ip()))
+            },
+        )
+}
@@ -1,8 +1,10 @@
 use super::prelude::*;
+use super::remote_addr;
 
 use crate::auth::{AuthInfo, Verifier};
+use crate::graphql::Loaders;
 use crate::service::GetUserByFirebaseIdRequest;
-use crate::service::{Context, ContextViewer, Service};
+use crate::service::{Context, Principal, Service};
 
 use warp::header::optional as header;
 use warp::path::full as full_path;
@@ -14,13 +16,14 @@ use warp::{any, Filter, Rejection, Reply};
 use graphql::http::playground_source;
 use graphql::http::GraphQLPlaygroundConfig as PlaygroundConfig;
 use graphql::Request as GraphQLRequest;
-use graphql::{ObjectType, Schema, SubscriptionType};
+use graphql::{Data, ObjectType, Schema, SubscriptionType};
 
 use graphql_warp::graphql as graphql_filter;
-use graphql_warp::graphql_subscription as graphql_subscription_filter;
+use graphql_warp::graphql_subscription_with_data as graphql_subscription_filter;
 use graphql_warp::Response as GraphQLResponse;
 
 use http::header::AUTHORIZATION;
+use std::net::IpAddr;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 
@@ -29,6 +32,7 @@ pub fn graphql<Q, M, S, V>(
     runtime: Arc<Runtime>,
     service: Arc<Service>,
     verifier: Arc<V>,
+    trust_forwarded_for: bool,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone
 where
     Q: ObjectType + Send + Sync + 'static,
@@ -37,22 +41,26 @@ where
     V: Verifier + 'static,
 {
     let graphql_runtime = runtime.clone();
+    let subscription_service = service.clone();
     let graphql = graphql_filter(schema.clone())
         .and(any().map(move || graphql_runtime.clone()))
         .and(any().map(move || service.clone()))
         .and(with_auth(runtime, verifier))
+        .and(remote_addr(trust_forwarded_for))
         .and_then(
             |(schema, request): (Schema<Q, M, S>, GraphQLRequest),
              runtime: Arc<Runtime>,
              service: Arc<Service>,
-             auth: Option<AuthInfo>| async move {
+             auth: Option<AuthInfo>,
+             remote_addr: Option<IpAddr>| async move {
                 let future = async move {
                     let mut request = request;
-                    let mut context = Context::default();
+                    let mut context = Context::anonymous();
 
                     if let Some(auth) = &auth {
-                        let firebase_id = auth.claims().user_id.clone();
+                        let firebase_id = auth.claims().account_id().to_owned();
                         let user = {
+                            let context = Context::system();
                             let request =
                                 GetUserByFirebaseIdRequest { firebase_id };
                             let response = service
@@ -63,13 +71,14 @@ where
                                 )?;
                             response.user
                         };
-                        let viewer = match user {
-                            Some(user) => ContextViewer::User(Box::new(user)),
-                            None => ContextViewer::Anonymous,
-                        };
-                        context.viewer = Some(viewer);
+                        if let Some(user) = user {
+                            context = Context::new(Principal::user(user));
+                        }
                     };
+                    context = context.with_remote_addr(remote_addr);
 
+                    let loaders = Loaders::new(service, context.clone());
+                    request = request.data(loaders);
                     request = request.data(context);
                     if let Some(auth) = auth {
                         request = request.data(auth);
@@ -87,10 +96,23 @@ where
                     .map_err(|error| custom(RouteError::from(error)))
             },
         );
-    let graphql_subscription = graphql_subscription_filter(schema);
+    let subscription_data =
+        move |_: JsonValue| subscription_data(subscription_service.clone());
+    let graphql_subscription =
+        graphql_subscription_filter(schema, Some(subscription_data));
     graphql.or(graphql_subscription)
 }
 
+// Subscriptions are not authenticated, so they are always served to an
+// anonymous viewer.
+fn subscription_data(service: Arc<Service>) -> graphql::Result<Data> {
+    let mut data = Data::default();
+    let context = Context::anonymous();
+    data.insert(Loaders::new(service, context.clone()));
+    data.insert(context);
+    Ok(data)
+}
+
 fn with_auth<V: Verifier + 'static>(
     runtime: Arc<Runtime>,
     verifier: Arc<V>,
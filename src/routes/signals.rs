@@ -0,0 +1,417 @@
+use super::prelude::*;
+use super::remote_addr;
+
+use crate::graphql::{Id, Signal};
+use crate::service::CapacityExceededError;
+use crate::service::CreateSignalMeasurementRequest;
+use crate::service::CreateSignalMeasurementsRequest;
+use crate::service::InvalidMeasurementError;
+use crate::service::PingSignalRequest;
+use crate::service::RateLimitedError;
+use crate::service::RejectedSignalObservation;
+use crate::service::VerifySignalSecretRequest;
+use crate::service::{Context, Principal, Service};
+use crate::service::{SignalMeasurement, SignalObservation};
+
+use warp::body::bytes as body_bytes;
+use warp::body::content_length_limit;
+use warp::header::optional as header;
+use warp::hyper::body::Bytes;
+use warp::path::{end as path_end, param as path_param};
+use warp::reject::custom;
+use warp::reply::{json as json_reply, reply, with_status};
+use warp::{any, path, post, Filter, Rejection, Reply};
+
+use http::header::{AUTHORIZATION, CONTENT_TYPE};
+use http::StatusCode;
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// The header with which a signal identifies a measurement, so that retries
+/// don't create duplicate measurements.
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// The largest measurement body that will be accepted, in bytes.
+const MAX_BODY_SIZE: u64 = 1024;
+
+/// The largest batch of observations that will be accepted, in bytes.
+const MAX_BATCH_BODY_SIZE: u64 = 64 * 1024;
+
+/// A batch of observations reported by a signal, as JSON.
+#[derive(Debug, Clone, Deserialize)]
+struct ObservationsBody {
+    observations: Vec<SignalObservation>,
+}
+
+/// The observations in a batch that weren't recorded, as JSON.
+#[derive(Debug, Clone, Serialize)]
+struct RejectedObservationsBody {
+    rejected: Vec<RejectedObservationBody>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RejectedObservationBody {
+    observed_at: DateTime,
+    measurement: u16,
+    reason: String,
+}
+
+/// A measurement reported by a signal, as either JSON or form data.
+///
+/// Exactly one of `measurement` (an absolute count) and `delta` (a change to
+/// the current count) must be given.
+#[derive(Debug, Clone, Deserialize)]
+struct MeasurementBody {
+    measurement: Option<u16>,
+    delta: Option<i32>,
+}
+
+impl TryFrom<MeasurementBody> for SignalMeasurement {
+    type Error = Error;
+
+    fn try_from(body: MeasurementBody) -> Result<Self, Self::Error> {
+        let MeasurementBody { measurement, delta } = body;
+        let measurement = match (measurement, delta) {
+            (Some(value), None) => SignalMeasurement::Absolute(value),
+            (None, Some(delta)) => SignalMeasurement::Delta(delta),
+            _ => bail!("exactly one of measurement and delta is required"),
+        };
+        Ok(measurement)
+    }
+}
+
+/// Accept measurements from signal devices at `/{id}/measurements`, where
+/// the signal's secret is given as the `Authorization` header.
+///
+/// Responds with `204 No Content` when the measurement is recorded, `400 Bad
+/// Request` when the signal ID or body is malformed, `401 Unauthorized` when
+/// the secret is missing or doesn't match, `403 Forbidden` when the signal has
+/// been disabled, `410 Gone` when the signal has been archived, `422
+/// Unprocessable Entity` when the measurement exceeds the shelter's capacity
+/// and the shelter rejects such measurements, and `429 Too Many Requests`
+/// when the signal or its address has exceeded its rate limit.
+///
+/// Measurements can be given an `Idempotency-Key` header, so that retrying
+/// them doesn't create duplicates.
+pub fn signal_measurements(
+    runtime: Arc<Runtime>,
+    service: Arc<Service>,
+    trust_forwarded_for: bool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    post()
+        .and(path_param::<String>())
+        .and(path("measurements"))
+        .and(path_end())
+        .and(header::<String>(
+            #[allow(clippy::borrow_interior_mutable_const)]
+            AUTHORIZATION.as_str(),
+        ))
+        .and(header::<String>(
+            #[allow(clippy::borrow_interior_mutable_const)]
+            CONTENT_TYPE.as_str(),
+        ))
+        .and(header::<String>(IDEMPOTENCY_KEY))
+        .and(content_length_limit(MAX_BODY_SIZE))
+        .and(body_bytes())
+        .and(remote_addr(trust_forwarded_for))
+        .and(any().map(move || runtime.clone()))
+        .and(any().map(move || service.clone()))
+        .and_then(create_signal_measurement)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_signal_measurement(
+    signal_id: String,
+    secret: Option<String>,
+    content_type: Option<String>,
+    idempotency_key: Option<String>,
+    body: Bytes,
+    remote_addr: Option<IpAddr>,
+    runtime: Arc<Runtime>,
+    service: Arc<Service>,
+) -> Result<impl Reply, Rejection> {
+    let measurement =
+        parse_body(content_type.as_deref(), &body).map_err(|_| {
+            custom(RouteError::new(StatusCode::BAD_REQUEST, "invalid body"))
+        })?;
+    let signal_id = parse_signal_id(&signal_id).map_err(|_| {
+        custom(RouteError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid signal ID",
+        ))
+    })?;
+    let secret = parse_secret(secret)?;
+
+    let future = async move {
+        let context =
+            authenticate(&service, signal_id, secret, remote_addr).await?;
+
+        // Create measurement.
+        let request = CreateSignalMeasurementRequest {
+            signal_id,
+            measurement,
+            idempotency_key,
+        };
+        service
+            .create_signal_measurement(&context, request)
+            .await
+            .map_err(|error| {
+                signal_error(error, "failed to create measurement")
+            })?;
+        Result::<_, RouteError>::Ok(())
+    };
+
+    runtime.spawn(future).await.unwrap().map_err(custom)?;
+    Ok(with_status(reply(), StatusCode::NO_CONTENT))
+}
+
+/// Accept batches of timestamped measurements from signal devices at
+/// `/{id}/measurements/batch`, as a JSON body like:
+///
+/// ```json
+/// {"observations": [{"observed_at": "2021-01-07T12:00:00Z", "measurement": 12}]}
+/// ```
+///
+/// Responds like `signal_measurements`, except that observations exceeding
+/// the shelter's capacity don't fail the batch when the shelter rejects
+/// them. Instead, the rest are recorded, and the response is `200 OK` with a
+/// JSON body listing those that weren't:
+///
+/// ```json
+/// {"rejected": [{"observed_at": "2021-01-07T12:00:00Z", "measurement": 99, "reason": "..."}]}
+/// ```
+pub fn signal_measurement_batches(
+    runtime: Arc<Runtime>,
+    service: Arc<Service>,
+    trust_forwarded_for: bool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    post()
+        .and(path_param::<String>())
+        .and(path("measurements"))
+        .and(path("batch"))
+        .and(path_end())
+        .and(header::<String>(
+            #[allow(clippy::borrow_interior_mutable_const)]
+            AUTHORIZATION.as_str(),
+        ))
+        .and(content_length_limit(MAX_BATCH_BODY_SIZE))
+        .and(body_bytes())
+        .and(remote_addr(trust_forwarded_for))
+        .and(any().map(move || runtime.clone()))
+        .and(any().map(move || service.clone()))
+        .and_then(create_signal_measurements)
+}
+
+async fn create_signal_measurements(
+    signal_id: String,
+    secret: Option<String>,
+    body: Bytes,
+    remote_addr: Option<IpAddr>,
+    runtime: Arc<Runtime>,
+    service: Arc<Service>,
+) -> Result<impl Reply, Rejection> {
+    let body: ObservationsBody = json::from_slice(&body).map_err(|_| {
+        custom(RouteError::new(StatusCode::BAD_REQUEST, "invalid body"))
+    })?;
+    let signal_id = parse_signal_id(&signal_id).map_err(|_| {
+        custom(RouteError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid signal ID",
+        ))
+    })?;
+    let secret = parse_secret(secret)?;
+
+    let future = async move {
+        let context =
+            authenticate(&service, signal_id, secret, remote_addr).await?;
+
+        // Create measurements.
+        let request = CreateSignalMeasurementsRequest {
+            signal_id,
+            observations: body.observations,
+        };
+        let response = service
+            .create_signal_measurements(&context, request)
+            .await
+            .map_err(|error| {
+                signal_error(error, "failed to create measurements")
+            })?;
+        Result::<_, RouteError>::Ok(response.rejected)
+    };
+
+    let rejected = runtime.spawn(future).await.unwrap().map_err(custom)?;
+    if rejected.is_empty() {
+        let reply = with_status(reply(), StatusCode::NO_CONTENT);
+        return Ok(reply.into_response());
+    }
+    let rejected = rejected
+        .into_iter()
+        .map(|rejected| {
+            let RejectedSignalObservation {
+                observation,
+                reason,
+            } = rejected;
+            let SignalObservation {
+                observed_at,
+                measurement,
+            } = observation;
+            RejectedObservationBody {
+                observed_at,
+                measurement,
+                reason,
+            }
+        })
+        .collect();
+    let body = RejectedObservationsBody { rejected };
+    Ok(json_reply(&body).into_response())
+}
+
+/// Accept heartbeats from signal devices at `/{id}/heartbeat`, which report
+/// that a signal is still online without measuring anything.
+///
+/// Responds like `signal_measurements`.
+pub fn signal_heartbeats(
+    runtime: Arc<Runtime>,
+    service: Arc<Service>,
+    trust_forwarded_for: bool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    post()
+        .and(path_param::<String>())
+        .and(path("heartbeat"))
+        .and(path_end())
+        .and(header::<String>(
+            #[allow(clippy::borrow_interior_mutable_const)]
+            AUTHORIZATION.as_str(),
+        ))
+        .and(remote_addr(trust_forwarded_for))
+        .and(any().map(move || runtime.clone()))
+        .and(any().map(move || service.clone()))
+        .and_then(ping_signal)
+}
+
+async fn ping_signal(
+    signal_id: String,
+    secret: Option<String>,
+    remote_addr: Option<IpAddr>,
+    runtime: Arc<Runtime>,
+    service: Arc<Service>,
+) -> Result<impl Reply, Rejection> {
+    let signal_id = parse_signal_id(&signal_id).map_err(|_| {
+        custom(RouteError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid signal ID",
+        ))
+    })?;
+    let secret = parse_secret(secret)?;
+
+    let future = async move {
+        let context =
+            authenticate(&service, signal_id, secret, remote_addr).await?;
+
+        // Record heartbeat.
+        let request = PingSignalRequest { signal_id };
+        service
+            .ping_signal(&context, request)
+            .await
+            .map_err(|error| {
+                signal_error(error, "failed to record heartbeat")
+            })?;
+        Result::<_, RouteError>::Ok(())
+    };
+
+    runtime.spawn(future).await.unwrap().map_err(custom)?;
+    Ok(with_status(reply(), StatusCode::NO_CONTENT))
+}
+
+/// Convert an error from a signal's request into a `RouteError`, which is a
+/// client error if the request was invalid, a measurement exceeded its
+/// shelter's capacity, or the signal or its address exceeded a rate limit.
+fn signal_error(error: Error, context: &'static str) -> RouteError {
+    if let Some(error) = error.downcast_ref::<InvalidMeasurementError>() {
+        return RouteError::new(StatusCode::BAD_REQUEST, &error.to_string());
+    }
+    if let Some(error) = error.downcast_ref::<CapacityExceededError>() {
+        return RouteError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &error.to_string(),
+        );
+    }
+    if let Some(error) = error.downcast_ref::<RateLimitedError>() {
+        return RouteError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            &error.to_string(),
+        );
+    }
+    error.context(context).into()
+}
+
+/// Authenticate as a signal, using its secret.
+async fn authenticate(
+    service: &Service,
+    signal_id: Uuid,
+    secret: String,
+    remote_addr: Option<IpAddr>,
+) -> Result<Context, RouteError> {
+    let context = Context::anonymous().with_remote_addr(remote_addr);
+    let request = VerifySignalSecretRequest { signal_id, secret };
+    let response = service
+        .verify_signal_secret(&context, request)
+        .await
+        .map_err(|error| signal_error(error, "failed to verify secret"))?;
+    if !response.is_valid {
+        let error = RouteError::new(StatusCode::UNAUTHORIZED, "not authorized");
+        return Err(error);
+    }
+    if response.is_archived {
+        let error = RouteError::new(StatusCode::GONE, "signal is archived");
+        return Err(error);
+    }
+    if response.is_disabled {
+        let error =
+            RouteError::new(StatusCode::FORBIDDEN, "signal is disabled");
+        return Err(error);
+    }
+    let context = Context::new(Principal::Signal(signal_id))
+        .with_remote_addr(remote_addr);
+    Ok(context)
+}
+
+/// Parse a signal secret from an `Authorization` header, which may be given
+/// as a bearer token.
+fn parse_secret(header: Option<String>) -> Result<String, Rejection> {
+    let secret = header.map(|secret| match secret.strip_prefix("Bearer ") {
+        Some(secret) => secret.to_owned(),
+        None => secret,
+    });
+    secret.ok_or_else(|| {
+        custom(RouteError::new(StatusCode::UNAUTHORIZED, "missing secret"))
+    })
+}
+
+/// Parse a measurement body, which is read as form data unless it is
+/// explicitly JSON.
+fn parse_body(
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<SignalMeasurement> {
+    let is_json = matches!(
+        content_type,
+        Some(content_type) if content_type.starts_with("application/json")
+    );
+    let body: MeasurementBody = if is_json {
+        json::from_slice(body)?
+    } else {
+        urlencoded::from_bytes(body)?
+    };
+    body.try_into()
+}
+
+/// Parse a signal ID, given either as a UUID or as a GraphQL `ID`.
+fn parse_signal_id(s: &str) -> Result<Uuid> {
+    if let Ok(signal_id) = s.parse() {
+        return Ok(signal_id);
+    }
+    let id: Id = s.parse()?;
+    id.get::<Signal>()
+}
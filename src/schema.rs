@@ -1,3 +1,18 @@
+table! {
+    measurement_anomalies (id) {
+        id -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        measurement_id -> Uuid,
+        shelter_id -> Uuid,
+        signal_id -> Uuid,
+        kind -> Text,
+        score -> Float8,
+        resolved_at -> Nullable<Timestamptz>,
+        resolved_by_id -> Nullable<Uuid>,
+    }
+}
+
 table! {
     shelter_measurements (id) {
         id -> Uuid,
@@ -8,7 +23,27 @@ table! {
         occupied_beds -> Int4,
         total_spots -> Int4,
         total_beds -> Int4,
-        signal_id -> Uuid,
+        signal_id -> Nullable<Uuid>,
+        idempotency_key -> Nullable<Text>,
+        value -> Nullable<Int4>,
+        status -> Text,
+        user_id -> Nullable<Uuid>,
+        retracted_at -> Nullable<Timestamptz>,
+        retracted_by_id -> Nullable<Uuid>,
+        retraction_reason -> Nullable<Text>,
+        correction_of_id -> Nullable<Uuid>,
+        delta -> Nullable<Int4>,
+    }
+}
+
+table! {
+    shelter_memberships (id) {
+        id -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        shelter_id -> Uuid,
+        user_id -> Uuid,
+        role -> Text,
     }
 }
 
@@ -24,7 +59,6 @@ table! {
         phone -> Text,
         website_url -> Nullable<Text>,
         address -> Jsonb,
-        location -> Jsonb,
         total_spots -> Int4,
         total_beds -> Int4,
         food -> Text,
@@ -32,6 +66,12 @@ table! {
         image_url -> Nullable<Text>,
         occupied_spots -> Nullable<Int4>,
         occupied_beds -> Nullable<Int4>,
+        latitude -> Float8,
+        longitude -> Float8,
+        aggregation -> Text,
+        primary_signal_id -> Nullable<Uuid>,
+        occupancy_updated_at -> Nullable<Timestamptz>,
+        capacity_policy -> Text,
     }
 }
 
@@ -44,7 +84,12 @@ table! {
         name -> Text,
         shelter_id -> Uuid,
         measure -> Text,
-        secret -> Text,
+        secret_hash -> Text,
+        previous_secret_hash -> Nullable<Text>,
+        previous_secret_expires_at -> Nullable<Timestamptz>,
+        archived_at -> Nullable<Timestamptz>,
+        last_seen_at -> Nullable<Timestamptz>,
+        disabled_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -67,12 +112,21 @@ table! {
     }
 }
 
+joinable!(measurement_anomalies -> shelter_measurements (measurement_id));
+joinable!(measurement_anomalies -> shelters (shelter_id));
+joinable!(measurement_anomalies -> signals (signal_id));
+joinable!(measurement_anomalies -> users (resolved_by_id));
 joinable!(shelter_measurements -> shelters (shelter_id));
 joinable!(shelter_measurements -> signals (signal_id));
+joinable!(shelter_measurements -> users (user_id));
+joinable!(shelter_memberships -> shelters (shelter_id));
+joinable!(shelter_memberships -> users (user_id));
 joinable!(signals -> shelters (shelter_id));
 
 allow_tables_to_appear_in_same_query!(
+    measurement_anomalies,
     shelter_measurements,
+    shelter_memberships,
     shelters,
     signals,
     users,
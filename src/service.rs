@@ -21,6 +21,9 @@ mod prelude {
 mod address;
 pub use address::*;
 
+mod bus;
+pub use bus::*;
+
 mod context;
 pub use context::*;
 
@@ -33,12 +36,21 @@ pub use self::geo::*;
 mod input;
 pub use input::*;
 
+mod measurement_anomaly;
+pub use measurement_anomaly::*;
+
 mod meta;
 pub use self::meta::*;
 
 mod phone;
 pub use phone::*;
 
+mod rate_limit;
+pub use rate_limit::*;
+
+mod secret;
+pub use secret::*;
+
 mod slug;
 pub use self::slug::*;
 
@@ -48,6 +60,9 @@ pub use shelter::*;
 mod shelter_measurement;
 pub use shelter_measurement::*;
 
+mod shelter_membership;
+pub use shelter_membership::*;
+
 mod signal;
 pub use signal::*;
 
@@ -60,6 +75,8 @@ pub use verifiable::*;
 use crate::db::PgPool;
 use crate::prelude::*;
 
+use std::net::IpAddr;
+
 // pub struct Config {}
 
 /// A `Service` implements the Chalmers Project API.
@@ -67,6 +84,27 @@ use crate::prelude::*;
 #[builder(build_fn(name = "build_internal", private))]
 pub struct Service {
     db_pool: PgPool,
+
+    #[builder(default)]
+    occupancy_bus: Bus<ShelterOccupancyEvent>,
+
+    /// How long a signal can go without reporting a measurement or
+    /// heartbeat before it is considered stale.
+    #[builder(default = "ChronoDuration::minutes(15)")]
+    signal_stale_after: ChronoDuration,
+
+    /// Limits how often each signal can report measurements.
+    #[builder(default = "RateLimiter::new(RateLimit::per_minute(60))")]
+    signal_rate_limiter: RateLimiter<Uuid>,
+
+    /// Limits how often measurements can be reported from each address (see
+    /// `rate_limited_address`), across all signals.
+    #[builder(default = "RateLimiter::new(RateLimit::per_minute(600))")]
+    address_rate_limiter: RateLimiter<IpAddr>,
+
+    /// Limits how often a signal can be throttled before it is disabled.
+    #[builder(default = "RateLimiter::new(RateLimit::per_hour(100))")]
+    signal_violation_limiter: RateLimiter<Uuid>,
 }
 
 impl Service {
@@ -0,0 +1,51 @@
+use super::prelude::*;
+
+use futures_util::stream::{unfold, BoxStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::{channel, Sender};
+
+const BUS_CAPACITY: usize = 256;
+
+/// A `Bus` broadcasts in-process events to all of its subscribers.
+#[derive(Debug, Clone)]
+pub struct Bus<T> {
+    sender: Sender<T>,
+}
+
+impl<T: Clone + Send + 'static> Bus<T> {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = channel(capacity);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: T) {
+        // Sending only fails when there are no subscribers, in which case
+        // there is no one to notify.
+        self.sender.send(event).ok();
+    }
+
+    pub fn subscribe(&self) -> BoxStream<'static, T> {
+        let receiver = self.sender.subscribe();
+        let events = unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "bus subscriber lagged ({} events skipped)",
+                            skipped
+                        )
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        events.boxed()
+    }
+}
+
+impl<T: Clone + Send + 'static> Default for Bus<T> {
+    fn default() -> Self {
+        Self::new(BUS_CAPACITY)
+    }
+}
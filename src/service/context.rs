@@ -1,38 +1,110 @@
 use super::prelude::*;
 
+use std::net::IpAddr;
+
 /// A request-scoped context.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct Context {
-    /// The current authenticated user.
-    pub viewer: Option<ContextViewer>,
+    /// The party on whose behalf the request is made.
+    pub principal: Principal,
+
+    /// The address the request was made from, if known.
+    pub remote_addr: Option<IpAddr>,
 }
 
+/// A `Principal` is the party on whose behalf a service request is made.
 #[derive(Debug, Clone)]
-pub enum ContextViewer {
+pub enum Principal {
+    /// The API itself, e.g. for lookups that back another request.
+    System,
+
+    /// An authenticated user with administrative rights.
+    Admin(Box<User>),
+
+    /// An authenticated user.
     User(Box<User>),
+
+    /// An unauthenticated caller, or one without a registered user.
     Anonymous,
+
+    /// An integration authenticated by the API key with the given ID, which
+    /// can read, but not change, shelters' signals and their measurements.
+    ApiKey(Uuid),
+
+    /// A signal device, authenticated by its secret.
+    Signal(Uuid),
+}
+
+impl Principal {
+    /// Resolve the principal for an authenticated user.
+    pub fn user(user: User) -> Self {
+        if user.is_admin {
+            Principal::Admin(Box::new(user))
+        } else {
+            Principal::User(Box::new(user))
+        }
+    }
 }
 
 impl Context {
+    pub fn new(principal: Principal) -> Self {
+        Context {
+            principal,
+            remote_addr: None,
+        }
+    }
+
+    pub fn with_remote_addr(self, remote_addr: Option<IpAddr>) -> Self {
+        Context {
+            remote_addr,
+            ..self
+        }
+    }
+
+    pub fn system() -> Self {
+        Self::new(Principal::System)
+    }
+
+    pub fn anonymous() -> Self {
+        Self::new(Principal::Anonymous)
+    }
+
+    /// Derive a context for calls made by the API itself on behalf of this
+    /// request.
     pub fn internal(&self) -> Context {
         let mut context = self.clone();
-        context.viewer = None;
+        context.principal = Principal::System;
         context
     }
 
-    pub fn is_internal(&self) -> bool {
-        match &self.viewer {
-            Some(ContextViewer::User(user)) => user.is_admin,
-            _ => true,
-        }
+    /// Whether the principal has administrative rights, which only the
+    /// system and admins do.
+    pub fn is_privileged(&self) -> bool {
+        matches!(self.principal, Principal::System | Principal::Admin(_))
     }
 }
 
 impl Context {
     pub fn viewing_user(&self) -> Option<&User> {
-        self.viewer.as_ref().and_then(|viewer| match viewer {
-            ContextViewer::User(user) => Some(user.as_ref()),
-            ContextViewer::Anonymous => None,
-        })
+        match &self.principal {
+            Principal::Admin(user) | Principal::User(user) => {
+                Some(user.as_ref())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn viewing_signal_id(&self) -> Option<Uuid> {
+        match &self.principal {
+            Principal::Signal(signal_id) => Some(*signal_id),
+            _ => None,
+        }
+    }
+
+    pub fn viewing_api_key_id(&self) -> Option<Uuid> {
+        match &self.principal {
+            Principal::ApiKey(api_key_id) => Some(*api_key_id),
+            _ => None,
+        }
     }
 }
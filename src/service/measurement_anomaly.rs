@@ -0,0 +1,533 @@
+use super::prelude::*;
+
+use models::MeasurementAnomaly as MeasurementAnomalyModel;
+
+use crate::db::functions::date_part;
+
+lazy_static! {
+    static ref SPIKE_WINDOW: ChronoDuration = ChronoDuration::minutes(5);
+    static ref FLATLINE_DURATION: ChronoDuration = ChronoDuration::hours(24);
+    static ref PROFILE_HISTORY: ChronoDuration = ChronoDuration::weeks(8);
+}
+
+/// The fraction of a shelter's capacity by which a reading must change
+/// within `SPIKE_WINDOW` to be considered a spike.
+const SPIKE_THRESHOLD: f64 = 0.5;
+
+/// The number of standard deviations by which a reading must differ from
+/// its hour-of-week profile to be considered atypical.
+const ATYPICAL_THRESHOLD: f64 = 3.0;
+
+/// The fewest readings from previous weeks with which to build an
+/// hour-of-week profile.
+const MIN_PROFILE_SAMPLES: usize = 12;
+
+/// A `MeasurementAnomaly` flags a `ShelterMeasurement` that doesn't look like
+/// a genuine reading, for an admin to review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeasurementAnomaly {
+    pub id: Uuid,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+
+    pub measurement_id: Uuid,
+    pub shelter_id: Uuid,
+    pub signal_id: Uuid,
+
+    pub kind: MeasurementAnomalyKind,
+
+    /// How far the measurement exceeded the threshold for its kind of
+    /// anomaly, such that `1.0` is just at the threshold.
+    pub score: f64,
+
+    pub resolved_at: Option<DateTime>,
+    pub resolved_by_id: Option<Uuid>,
+}
+
+/// A `MeasurementAnomalyKind` describes how a measurement is anomalous.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeasurementAnomalyKind {
+    /// The reading changed by much of the shelter's capacity within a few
+    /// minutes.
+    Spike,
+
+    /// The reading has been the same for over a day.
+    Flatline,
+
+    /// The reading differs greatly from the signal's readings at the same
+    /// hour of the week in previous weeks.
+    Atypical,
+}
+
+impl Display for MeasurementAnomalyKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let s = to_plain_string(self).map_err(|_| FmtError)?;
+        s.fmt(f)
+    }
+}
+
+impl FromStr for MeasurementAnomalyKind {
+    type Err = SerdePlainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_plain_str(s)
+    }
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct ListMeasurementAnomaliesRequest {
+    pub limit: u32,
+    pub offset: u32,
+    pub include_resolved: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListMeasurementAnomaliesResponse {
+    pub anomalies: Vec<MeasurementAnomaly>,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct CountMeasurementAnomaliesRequest {
+    pub include_resolved: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountMeasurementAnomaliesResponse {
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct ResolveMeasurementAnomalyRequest {
+    pub anomaly_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolveMeasurementAnomalyResponse {
+    pub anomaly: MeasurementAnomaly,
+}
+
+impl Service {
+    pub(super) async fn can_view_measurement_anomalies(
+        &self,
+        context: &Context,
+    ) -> Result<bool> {
+        // Only admins can view anomalies.
+        Ok(context.is_privileged())
+    }
+
+    pub(super) async fn can_resolve_measurement_anomalies(
+        &self,
+        context: &Context,
+    ) -> Result<bool> {
+        // Only admins can resolve anomalies.
+        Ok(context.is_privileged())
+    }
+
+    /// Score measurements reported by a signal for anomalies, and record any
+    /// that are found.
+    ///
+    /// Scoring looks up the signal's history, so it's done in the
+    /// background rather than holding up the report.
+    pub(super) fn internal_detect_measurement_anomalies(
+        &self,
+        signal: &Signal,
+        measurements: Vec<ShelterMeasurement>,
+    ) {
+        let pool = self.db_pool.clone();
+        let signal_id = signal.id;
+        let measure = signal.measure.clone();
+        spawn_blocking(move || {
+            let result = pool
+                .get()
+                .context("database connection failure")
+                .and_then(|conn| {
+                    detect_measurement_anomalies(
+                        &conn,
+                        signal_id,
+                        &measure,
+                        &measurements,
+                    )
+                });
+            if let Err(error) = result {
+                warn!("failed to detect measurement anomalies: {:#}", error);
+            }
+        });
+    }
+}
+
+impl Service {
+    pub async fn list_measurement_anomalies(
+        &self,
+        context: &Context,
+        request: ListMeasurementAnomaliesRequest,
+    ) -> Result<ListMeasurementAnomaliesResponse> {
+        let ListMeasurementAnomaliesRequest {
+            limit,
+            offset,
+            include_resolved,
+        } = request;
+
+        if !self.can_view_measurement_anomalies(context).await? {
+            bail!("not authorized")
+        }
+
+        let anomalies = {
+            let pool = self.db_pool.clone();
+            let anomalies = spawn_blocking(
+                move || -> Result<Vec<MeasurementAnomalyModel>> {
+                    use schema::measurement_anomalies as anomalies;
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    let mut query = anomalies::table.into_boxed();
+                    if !include_resolved {
+                        query = query.filter(anomalies::resolved_at.is_null());
+                    }
+                    query
+                        .order((
+                            anomalies::created_at.desc(),
+                            anomalies::id.desc(),
+                        ))
+                        .limit(limit.into())
+                        .offset(offset.into())
+                        .load(&conn)
+                        .context("failed to load anomaly models")
+                },
+            )
+            .await
+            .unwrap()?;
+            anomalies
+                .into_iter()
+                .map(MeasurementAnomaly::try_from)
+                .collect::<Result<Vec<_>>>()
+                .context("failed to decode anomaly models")?
+        };
+
+        let response = ListMeasurementAnomaliesResponse { anomalies };
+        Ok(response)
+    }
+
+    pub async fn count_measurement_anomalies(
+        &self,
+        context: &Context,
+        request: CountMeasurementAnomaliesRequest,
+    ) -> Result<CountMeasurementAnomaliesResponse> {
+        let CountMeasurementAnomaliesRequest { include_resolved } = request;
+
+        if !self.can_view_measurement_anomalies(context).await? {
+            bail!("not authorized")
+        }
+
+        let count = {
+            let pool = self.db_pool.clone();
+            let count = spawn_blocking(move || -> Result<i64> {
+                use schema::measurement_anomalies as anomalies;
+                let conn = pool.get().context("database connection failure")?;
+                let mut query = anomalies::table.into_boxed();
+                if !include_resolved {
+                    query = query.filter(anomalies::resolved_at.is_null());
+                }
+                query
+                    .count()
+                    .get_result(&conn)
+                    .context("failed to count anomalies")
+            })
+            .await
+            .unwrap()?;
+            count.try_into().context("invalid count")?
+        };
+
+        let response = CountMeasurementAnomaliesResponse { count };
+        Ok(response)
+    }
+
+    pub async fn resolve_measurement_anomaly(
+        &self,
+        context: &Context,
+        request: ResolveMeasurementAnomalyRequest,
+    ) -> Result<ResolveMeasurementAnomalyResponse> {
+        let ResolveMeasurementAnomalyRequest { anomaly_id } = request;
+
+        if !self.can_resolve_measurement_anomalies(context).await? {
+            bail!("not authorized")
+        }
+
+        // Resolve anomaly, unless it was already resolved.
+        let anomaly = {
+            let pool = self.db_pool.clone();
+            let user_id = context.viewing_user().map(|user| user.id);
+            let anomaly =
+                spawn_blocking(move || -> Result<MeasurementAnomalyModel> {
+                    use schema::measurement_anomalies as anomalies;
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    let now = Utc::now();
+                    update(
+                        anomalies::table
+                            .find(anomaly_id)
+                            .filter(anomalies::resolved_at.is_null()),
+                    )
+                    .set((
+                        anomalies::resolved_at.eq(now),
+                        anomalies::resolved_by_id.eq(user_id),
+                        anomalies::updated_at.eq(now),
+                    ))
+                    .execute(&conn)
+                    .context("failed to update anomaly model")?;
+                    anomalies::table
+                        .find(anomaly_id)
+                        .first(&conn)
+                        .optional()
+                        .context("failed to load anomaly model")?
+                        .context("anomaly not found")
+                })
+                .await
+                .unwrap()?;
+            MeasurementAnomaly::try_from(anomaly)
+                .context("failed to decode anomaly model")?
+        };
+
+        let response = ResolveMeasurementAnomalyResponse { anomaly };
+        Ok(response)
+    }
+}
+
+/// Score measurements reported by a signal for anomalies, and record any
+/// that are found.
+fn detect_measurement_anomalies(
+    conn: &PgConnection,
+    signal_id: Uuid,
+    measure: &ShelterMeasure,
+    measurements: &[ShelterMeasurement],
+) -> Result<Vec<MeasurementAnomaly>> {
+    use schema::measurement_anomalies as anomalies;
+
+    let mut detected = Vec::new();
+    for measurement in measurements {
+        // Readings over capacity are already flagged.
+        if measurement.status == ShelterMeasurementStatus::Flagged {
+            continue;
+        }
+        let reading = match measurement.value {
+            Some(value) => SignalReading {
+                signal_id,
+                created_at: measurement.created_at,
+                value,
+            },
+            None => continue,
+        };
+
+        let capacity = measure.get(&measurement.capacity);
+        let scores = vec![
+            (
+                MeasurementAnomalyKind::Spike,
+                score_spike(conn, &reading, capacity)?,
+            ),
+            (
+                MeasurementAnomalyKind::Flatline,
+                score_flatline(conn, &reading)?,
+            ),
+            (
+                MeasurementAnomalyKind::Atypical,
+                score_atypical(conn, &reading)?,
+            ),
+        ];
+        for (kind, score) in scores {
+            let score = match score {
+                Some(score) => score,
+                None => continue,
+            };
+            let Meta {
+                id,
+                created_at,
+                updated_at,
+            } = Meta::new();
+            let anomaly = MeasurementAnomaly {
+                id,
+                created_at,
+                updated_at,
+
+                measurement_id: measurement.id,
+                shelter_id: measurement.shelter_id,
+                signal_id,
+
+                kind,
+                score,
+
+                resolved_at: None,
+                resolved_by_id: None,
+            };
+
+            // Record each anomaly as it's found, so that later
+            // measurements in the batch are scored against it.
+            insert_into(anomalies::table)
+                .values(MeasurementAnomalyModel::from(anomaly.clone()))
+                .execute(conn)
+                .context("failed to insert anomaly model")?;
+            detected.push(anomaly);
+        }
+    }
+    Ok(detected)
+}
+
+/// Score how sharply a measurement changed from the signal's previous
+/// reading, if it was recent.
+fn score_spike(
+    conn: &PgConnection,
+    reading: &SignalReading,
+    capacity: u16,
+) -> Result<Option<f64>> {
+    use schema::shelter_measurements as measurements;
+
+    let previous: Option<(DateTime, Option<i32>)> = measurements::table
+        .filter(measurements::signal_id.eq(reading.signal_id))
+        .filter(measurements::retracted_at.is_null())
+        .filter(
+            measurements::status
+                .ne(ShelterMeasurementStatus::Flagged.to_string()),
+        )
+        .filter(measurements::created_at.lt(reading.created_at))
+        .order((measurements::created_at.desc(), measurements::id.desc()))
+        .select((measurements::created_at, measurements::value))
+        .first(conn)
+        .optional()
+        .context("failed to load previous measurement")?;
+    let (previous_at, previous) = match previous {
+        Some((previous_at, Some(previous))) => (previous_at, previous),
+        _ => return Ok(None),
+    };
+    if reading.created_at - previous_at > *SPIKE_WINDOW {
+        return Ok(None);
+    }
+
+    let change = (i32::from(reading.value) - previous).abs();
+    let change = f64::from(change) / f64::from(capacity.max(1));
+    if change < SPIKE_THRESHOLD {
+        return Ok(None);
+    }
+    Ok(Some(change / SPIKE_THRESHOLD))
+}
+
+/// Score how long the signal's reading has stayed the same, unless it was
+/// already flagged for doing so.
+fn score_flatline(
+    conn: &PgConnection,
+    reading: &SignalReading,
+) -> Result<Option<f64>> {
+    use diesel::expression::dsl::min;
+    use schema::measurement_anomalies as anomalies;
+    use schema::shelter_measurements as measurements;
+
+    let value = i32::from(reading.value);
+    let flagged = ShelterMeasurementStatus::Flagged.to_string();
+
+    // Find when the reading last changed.
+    let changed_at: Option<DateTime> = measurements::table
+        .filter(measurements::signal_id.eq(reading.signal_id))
+        .filter(measurements::retracted_at.is_null())
+        .filter(measurements::status.ne(&flagged))
+        .filter(measurements::created_at.lt(reading.created_at))
+        .filter(measurements::value.ne(value))
+        .select(measurements::created_at)
+        .order(measurements::created_at.desc())
+        .first(conn)
+        .optional()
+        .context("failed to load last change")?;
+
+    // Find the first reading since then.
+    let mut query = measurements::table
+        .filter(measurements::signal_id.eq(reading.signal_id))
+        .filter(measurements::retracted_at.is_null())
+        .filter(measurements::status.ne(&flagged))
+        .filter(measurements::created_at.lt(reading.created_at))
+        .into_boxed();
+    if let Some(changed_at) = changed_at {
+        query = query.filter(measurements::created_at.gt(changed_at));
+    }
+    let since: Option<DateTime> = query
+        .select(min(measurements::created_at))
+        .first(conn)
+        .context("failed to load start of flatline")?;
+    let since = match since {
+        Some(since) => since,
+        None => return Ok(None),
+    };
+
+    let duration = reading.created_at - since;
+    if duration < *FLATLINE_DURATION {
+        return Ok(None);
+    }
+
+    // Only flag each flatline once.
+    let flagged: i64 = anomalies::table
+        .inner_join(measurements::table)
+        .filter(anomalies::signal_id.eq(reading.signal_id))
+        .filter(
+            anomalies::kind.eq(MeasurementAnomalyKind::Flatline.to_string()),
+        )
+        .filter(measurements::created_at.ge(since))
+        .count()
+        .get_result(conn)
+        .context("failed to count flatline anomalies")?;
+    if flagged > 0 {
+        return Ok(None);
+    }
+
+    let score =
+        duration.num_seconds() as f64 / FLATLINE_DURATION.num_seconds() as f64;
+    Ok(Some(score))
+}
+
+/// Score how far a measurement deviates from the signal's readings at the
+/// same hour of the week in previous weeks.
+fn score_atypical(
+    conn: &PgConnection,
+    reading: &SignalReading,
+) -> Result<Option<f64>> {
+    use schema::shelter_measurements as measurements;
+
+    let created_at = reading.created_at;
+    let samples: Vec<Option<i32>> = measurements::table
+        .filter(measurements::signal_id.eq(reading.signal_id))
+        .filter(measurements::retracted_at.is_null())
+        .filter(
+            measurements::status
+                .ne(ShelterMeasurementStatus::Flagged.to_string()),
+        )
+        .filter(measurements::created_at.ge(created_at - *PROFILE_HISTORY))
+        .filter(
+            measurements::created_at.lt(created_at - ChronoDuration::days(1)),
+        )
+        .filter(
+            date_part("dow", measurements::created_at)
+                .eq(date_part("dow", created_at)),
+        )
+        .filter(
+            date_part("hour", measurements::created_at)
+                .eq(date_part("hour", created_at)),
+        )
+        .select(measurements::value)
+        .load(conn)
+        .context("failed to load hour-of-week profile")?;
+    let samples: Vec<i32> = samples.into_iter().flatten().collect();
+    if samples.len() < MIN_PROFILE_SAMPLES {
+        return Ok(None);
+    }
+
+    let count = samples.len() as f64;
+    let mean = samples.iter().copied().map(f64::from).sum::<f64>() / count;
+    let variance = samples
+        .iter()
+        .map(|&sample| (f64::from(sample) - mean).powi(2))
+        .sum::<f64>()
+        / count;
+
+    // Allow for a deviation of at least one person, so that signals with
+    // very regular readings aren't flagged for the smallest change.
+    let deviation =
+        (f64::from(reading.value) - mean).abs() / variance.sqrt().max(1.0);
+    if deviation < ATYPICAL_THRESHOLD {
+        return Ok(None);
+    }
+    Ok(Some(deviation / ATYPICAL_THRESHOLD))
+}
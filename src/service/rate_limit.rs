@@ -0,0 +1,264 @@
+use super::prelude::*;
+
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv6Addr};
+use std::time::Instant;
+
+/// The number of buckets a `RateLimiter` tracks before it forgets the least
+/// recently used ones.
+const MAX_BUCKETS: usize = 10_000;
+
+/// A `RateLimit` allows bursts of up to `capacity` calls, refilling at a rate
+/// of `capacity` calls per `period`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub period: Duration,
+}
+
+impl RateLimit {
+    pub fn per_minute(capacity: u32) -> Self {
+        Self {
+            capacity,
+            period: Duration::from_secs(60),
+        }
+    }
+
+    pub fn per_hour(capacity: u32) -> Self {
+        Self {
+            capacity,
+            period: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+/// A `RateLimiter` applies a `RateLimit` to each key separately, using a
+/// token bucket per key.
+#[derive(Debug, Clone)]
+pub struct RateLimiter<K> {
+    limit: RateLimit,
+    buckets: Arc<Mutex<Buckets<K>>>,
+}
+
+#[derive(Debug)]
+struct Buckets<K> {
+    buckets: Map<K, Bucket>,
+
+    /// The keys of the buckets, ordered from least to most recently used.
+    keys: BTreeMap<u64, K>,
+    uses: u64,
+}
+
+impl<K> Default for Buckets<K> {
+    fn default() -> Self {
+        Self {
+            buckets: Default::default(),
+            keys: Default::default(),
+            uses: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    used: u64,
+}
+
+impl<K: Hash + Eq + Clone> RateLimiter<K> {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            buckets: Default::default(),
+        }
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Take a token from the key's bucket, returning `false` if there are
+    /// none left.
+    pub fn check(&self, key: &K) -> bool {
+        self.check_at(key, Instant::now())
+    }
+
+    /// Like `check`, but as of the given time.
+    fn check_at(&self, key: &K, now: Instant) -> bool {
+        let RateLimit { capacity, period } = self.limit;
+        let capacity = capacity as f64;
+        let rate = capacity / period.as_secs_f64();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let Buckets {
+            buckets,
+            keys,
+            uses,
+        } = &mut *buckets;
+
+        // Take the key's bucket out of the usage order, or else make room
+        // for it by forgetting the least recently used bucket, which has
+        // had the longest to refill.
+        match buckets.get(key) {
+            Some(bucket) => {
+                keys.remove(&bucket.used);
+            }
+            None if buckets.len() >= MAX_BUCKETS => {
+                let used = keys.keys().next().copied();
+                if let Some(key) = used.and_then(|used| keys.remove(&used)) {
+                    buckets.remove(&key);
+                }
+            }
+            None => {}
+        }
+        *uses += 1;
+        keys.insert(*uses, key.clone());
+
+        let bucket = buckets.entry(key.clone()).or_insert(Bucket {
+            tokens: capacity,
+            refilled_at: now,
+            used: *uses,
+        });
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * rate).min(capacity);
+        bucket.refilled_at = now;
+        bucket.used = *uses;
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+
+    /// Refill the key's bucket.
+    pub fn reset(&self, key: &K) {
+        let mut buckets = self.buckets.lock().unwrap();
+        let Buckets { buckets, keys, .. } = &mut *buckets;
+        if let Some(bucket) = buckets.remove(key) {
+            keys.remove(&bucket.used);
+        }
+    }
+}
+
+/// The part of an address that requests from it are rate limited by.
+///
+/// IPv6 hosts are usually assigned a whole /64 prefix, so they are limited by
+/// that instead of by their full address.
+pub fn rate_limited_address(addr: IpAddr) -> IpAddr {
+    match addr {
+        IpAddr::V4(_) => addr,
+        IpAddr::V6(addr) => match addr.to_ipv4() {
+            Some(addr) => IpAddr::V4(addr),
+            None => {
+                let [a, b, c, d, ..] = addr.segments();
+                IpAddr::V6(Ipv6Addr::new(a, b, c, d, 0, 0, 0, 0))
+            }
+        },
+    }
+}
+
+/// The error returned when a caller has exceeded a `RateLimit`.
+#[derive(Debug, Clone)]
+pub struct RateLimitedError {
+    pub limit: RateLimit,
+}
+
+impl Display for RateLimitedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let RateLimit { capacity, period } = self.limit;
+        write!(
+            f,
+            "rate limit of {} requests per {} seconds exceeded",
+            capacity,
+            period.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for RateLimitedError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(capacity: u32, period_ms: u64) -> RateLimit {
+        RateLimit {
+            capacity,
+            period: Duration::from_millis(period_ms),
+        }
+    }
+
+    #[test]
+    fn allows_bursts_up_to_capacity() {
+        let limiter = RateLimiter::new(RateLimit::per_minute(3));
+        assert!(limiter.check(&1));
+        assert!(limiter.check(&1));
+        assert!(limiter.check(&1));
+        assert!(!limiter.check(&1));
+    }
+
+    #[test]
+    fn limits_keys_separately() {
+        let limiter = RateLimiter::new(RateLimit::per_minute(1));
+        assert!(limiter.check(&1));
+        assert!(!limiter.check(&1));
+        assert!(limiter.check(&2));
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let limiter = RateLimiter::new(limit(2, 100));
+        let start = Instant::now();
+        assert!(limiter.check_at(&1, start));
+        assert!(limiter.check_at(&1, start));
+        assert!(!limiter.check_at(&1, start));
+
+        // Half the period refills one token, but not two.
+        let now = start + Duration::from_millis(50);
+        assert!(limiter.check_at(&1, now));
+        assert!(!limiter.check_at(&1, now));
+
+        // A whole period refills the bucket, but not beyond its capacity.
+        let now = now + Duration::from_millis(500);
+        assert!(limiter.check_at(&1, now));
+        assert!(limiter.check_at(&1, now));
+        assert!(!limiter.check_at(&1, now));
+    }
+
+    #[test]
+    fn reset_refills_bucket() {
+        let limiter = RateLimiter::new(RateLimit::per_hour(1));
+        assert!(limiter.check(&1));
+        assert!(!limiter.check(&1));
+        limiter.reset(&1);
+        assert!(limiter.check(&1));
+    }
+
+    #[test]
+    fn forgets_least_recently_used_buckets() {
+        let limiter = RateLimiter::new(RateLimit::per_hour(1));
+        let now = Instant::now();
+        for key in 0..MAX_BUCKETS {
+            assert!(limiter.check_at(&key, now));
+        }
+
+        // Using the first bucket again makes the second the least recently
+        // used, which is forgotten to make room for another.
+        assert!(!limiter.check_at(&0, now));
+        assert!(limiter.check_at(&MAX_BUCKETS, now));
+        assert_eq!(limiter.buckets.lock().unwrap().buckets.len(), MAX_BUCKETS);
+        assert!(!limiter.check_at(&0, now));
+        assert!(limiter.check_at(&1, now));
+    }
+
+    #[test]
+    fn limits_ipv6_addresses_by_prefix() {
+        let addr = |addr: &str| rate_limited_address(addr.parse().unwrap());
+        assert_eq!(addr("2001:db8:1:2:3:4:5:6"), addr("2001:db8:1:2::"));
+        assert_ne!(addr("2001:db8:1:2::"), addr("2001:db8:1:3::"));
+        assert_eq!(addr("::ffff:192.0.2.1"), addr("192.0.2.1"));
+        assert_ne!(addr("192.0.2.1"), addr("192.0.2.2"));
+    }
+}
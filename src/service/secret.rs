@@ -0,0 +1,141 @@
+use super::prelude::*;
+
+use base64::decode as decode_base64;
+use base64::encode as encode_base64;
+use base64::encode_config as encode_base64_config;
+use base64::URL_SAFE_NO_PAD;
+
+use ::rand::random;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+const SECRET_HASH_SCHEME: &str = "sha256";
+
+/// Generate a new random secret.
+pub fn generate_secret() -> String {
+    let bytes: [u8; 32] = random();
+    encode_base64_config(bytes, URL_SAFE_NO_PAD)
+}
+
+/// A `SecretHash` is a salted digest of a secret, which can verify the
+/// secret without storing it.
+///
+/// Secrets are random and high-entropy, so a fast hash suffices.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct SecretHash {
+    salt: Vec<u8>,
+    digest: Vec<u8>,
+}
+
+impl SecretHash {
+    pub fn new(secret: &str) -> Self {
+        let salt: [u8; 16] = random();
+        let salt = salt.to_vec();
+        let digest = digest_secret(&salt, secret);
+        Self { salt, digest }
+    }
+
+    /// Check a secret against this hash, in constant time.
+    pub fn verify(&self, secret: &str) -> bool {
+        let digest = digest_secret(&self.salt, secret);
+        digest.ct_eq(&self.digest).into()
+    }
+}
+
+fn digest_secret(salt: &[u8], secret: &str) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(secret.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+impl Display for SecretHash {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let salt = encode_base64(&self.salt);
+        let digest = encode_base64(&self.digest);
+        write!(f, "{}:{}:{}", SECRET_HASH_SCHEME, salt, digest)
+    }
+}
+
+impl FromStr for SecretHash {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let (salt, digest) = match *parts.as_slice() {
+            [SECRET_HASH_SCHEME, salt, digest] => (salt, digest),
+            [scheme, _, _] => bail!("unknown scheme {}", scheme),
+            _ => bail!("bad format"),
+        };
+        let salt = decode_base64(salt).context("failed to decode salt")?;
+        let digest =
+            decode_base64(digest).context("failed to decode digest")?;
+        Ok(Self { salt, digest })
+    }
+}
+
+impl From<SecretHash> for String {
+    fn from(hash: SecretHash) -> Self {
+        hash.to_string()
+    }
+}
+
+impl TryFrom<String> for SecretHash {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_secrets_are_url_safe() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), 43);
+        assert!(secret
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_ne!(secret, generate_secret());
+    }
+
+    #[test]
+    fn hash_verifies_only_its_secret() {
+        let hash = SecretHash::new("correct horse");
+        assert!(hash.verify("correct horse"));
+        assert!(!hash.verify("correct horse "));
+        assert!(!hash.verify(""));
+    }
+
+    #[test]
+    fn hashes_are_salted() {
+        let hash = SecretHash::new("secret");
+        let other = SecretHash::new("secret");
+        assert_ne!(hash, other);
+        assert!(other.verify("secret"));
+    }
+
+    #[test]
+    fn hash_round_trips() {
+        let hash = SecretHash::new("secret");
+        let s = hash.to_string();
+        assert!(s.starts_with("sha256:"));
+        assert_eq!(s.split(':').count(), 3);
+
+        let parsed: SecretHash = s.parse().unwrap();
+        assert_eq!(parsed, hash);
+        assert!(parsed.verify("secret"));
+    }
+
+    #[test]
+    fn malformed_hashes_are_rejected() {
+        assert!("".parse::<SecretHash>().is_err());
+        assert!("sha256:c2FsdA==".parse::<SecretHash>().is_err());
+        assert!("md5:c2FsdA==:ZGlnZXN0".parse::<SecretHash>().is_err());
+        assert!("sha256:!!!:ZGlnZXN0".parse::<SecretHash>().is_err());
+    }
+}
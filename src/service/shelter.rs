@@ -1,8 +1,29 @@
 use super::prelude::*;
 
 use models::Shelter as ShelterModel;
+use models::ShelterMeasurement as ShelterMeasurementModel;
 use models::Signal as SignalModel;
 
+use models::SHELTER_COLUMNS;
+
+use crate::db::functions::{coalesce, jsonb_extract_path_text, lower};
+use crate::db::functions::{earth_box, ll_to_earth, CubeContains};
+use diesel::pg::Pg;
+use schema::shelters::BoxedQuery as SheltersQuery;
+
+use futures_util::future::ready;
+use futures_util::stream::BoxStream;
+
+use ::geo::algorithm::haversine_distance::HaversineDistance;
+use ::geo::Point;
+
+const MAX_SEARCH_RADIUS_KM: f64 = 500.0;
+
+/// How much to pad the search radius by when looking up nearby shelters, to
+/// make up for the `earthdistance` extension assuming a slightly larger earth
+/// than the haversine distance does.
+const SEARCH_RADIUS_MARGIN: f64 = 1.01;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Shelter {
     pub id: Uuid,
@@ -22,12 +43,21 @@ pub struct Shelter {
     pub location: Coordinate,
 
     pub capacity: ShelterSpace,
+    pub capacity_policy: ShelterCapacityPolicy,
     pub occupancy: Option<ShelterSpace>,
+
+    /// When the shelter's occupancy was last measured by a signal.
+    pub occupancy_updated_at: Option<DateTime>,
+
+    pub aggregation: ShelterAggregation,
+    pub primary_signal_id: Option<Uuid>,
     pub food: ShelterFood,
     pub tags: Set<ShelterTag>,
 }
 
-#[derive(Debug, Clone, Hash, Default, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Hash, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
 pub struct ShelterSpace {
     pub spots: u16,
     pub beds: u16,
@@ -83,6 +113,186 @@ impl FromStr for ShelterFood {
     }
 }
 
+/// A `ShelterCapacityPolicy` determines what happens to signal readings
+/// that exceed a `Shelter`'s capacity.
+///
+/// Where readings are aggregated into an occupancy that exceeds capacity,
+/// the occupancy is clamped, except that under `Reject` a reading reported
+/// as it happens is refused instead.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShelterCapacityPolicy {
+    /// Refuse the reading, and record nothing.
+    Reject,
+
+    /// Reduce the reading to the shelter's capacity.
+    Clamp,
+
+    /// Record the reading as flagged, without it affecting the shelter's
+    /// occupancy.
+    Flag,
+}
+
+// Deriving `Default` for enums requires a newer toolchain.
+#[allow(clippy::derivable_impls)]
+impl Default for ShelterCapacityPolicy {
+    fn default() -> Self {
+        ShelterCapacityPolicy::Clamp
+    }
+}
+
+impl Display for ShelterCapacityPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let s = to_plain_string(self).map_err(|_| FmtError)?;
+        s.fmt(f)
+    }
+}
+
+impl FromStr for ShelterCapacityPolicy {
+    type Err = SerdePlainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_plain_str(s)
+    }
+}
+
+impl ShelterCapacityPolicy {
+    /// Validate a signal's reading against a capacity, returning the value
+    /// to record and its status.
+    pub fn apply(
+        &self,
+        reading: i64,
+        capacity: u16,
+    ) -> Result<(u16, ShelterMeasurementStatus)> {
+        use ShelterMeasurementStatus::*;
+        if reading < 0 {
+            return Ok((0, Clamped));
+        }
+        if reading <= capacity.into() {
+            return Ok((reading as u16, Accepted));
+        }
+        match self {
+            ShelterCapacityPolicy::Reject => {
+                let error = CapacityExceededError { reading, capacity };
+                Err(error.into())
+            }
+            ShelterCapacityPolicy::Clamp => Ok((capacity, Clamped)),
+            ShelterCapacityPolicy::Flag => {
+                let value = reading.min(u16::MAX.into()) as u16;
+                Ok((value, Flagged))
+            }
+        }
+    }
+}
+
+impl ShelterCapacityPolicy {
+    /// Validate an occupancy aggregated from signals' readings against a
+    /// capacity, returning the occupancy to record and whether it was
+    /// clamped.
+    pub fn apply_aggregate(
+        &self,
+        aggregate: u16,
+        capacity: u16,
+    ) -> Result<(u16, bool)> {
+        if aggregate <= capacity {
+            return Ok((aggregate, false));
+        }
+        match self {
+            ShelterCapacityPolicy::Reject => {
+                let reading = aggregate.into();
+                let error = CapacityExceededError { reading, capacity };
+                Err(error.into())
+            }
+            _ => Ok((capacity, true)),
+        }
+    }
+
+    /// Validate an occupancy reported by hand against a capacity, returning
+    /// the occupancy to record and its status.
+    pub fn apply_space(
+        &self,
+        occupancy: &ShelterSpace,
+        capacity: &ShelterSpace,
+    ) -> Result<(ShelterSpace, ShelterMeasurementStatus)> {
+        use ShelterMeasurementStatus::*;
+        let (spots, spots_status) =
+            self.apply(occupancy.spots.into(), capacity.spots)?;
+        let (beds, beds_status) =
+            self.apply(occupancy.beds.into(), capacity.beds)?;
+        let status = match (spots_status, beds_status) {
+            (Flagged, _) | (_, Flagged) => Flagged,
+            (Clamped, _) | (_, Clamped) => Clamped,
+            _ => Accepted,
+        };
+        Ok((ShelterSpace { spots, beds }, status))
+    }
+}
+
+/// The error returned when a signal's reading exceeds its shelter's
+/// capacity, under `ShelterCapacityPolicy::Reject`.
+#[derive(Debug, Clone)]
+pub struct CapacityExceededError {
+    pub reading: i64,
+    pub capacity: u16,
+}
+
+impl Display for CapacityExceededError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let CapacityExceededError { reading, capacity } = self;
+        write!(
+            f,
+            "reading of {} exceeds shelter capacity of {}",
+            reading, capacity
+        )
+    }
+}
+
+impl std::error::Error for CapacityExceededError {}
+
+/// A `ShelterAggregation` determines how a `Shelter`'s occupancy is computed
+/// from the latest readings of its active signals.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShelterAggregation {
+    /// Use the most recent reading. Delta measurements adjust the shelter's
+    /// current occupancy.
+    Latest,
+
+    /// Add up the readings, such as those of counters at different
+    /// entrances. Delta measurements adjust the signal's own reading.
+    Sum,
+
+    /// Use the largest reading.
+    Max,
+
+    /// Use the reading of the shelter's primary signal, or the most recent
+    /// reading if it has none.
+    Primary,
+}
+
+// Deriving `Default` for enums requires a newer toolchain.
+#[allow(clippy::derivable_impls)]
+impl Default for ShelterAggregation {
+    fn default() -> Self {
+        ShelterAggregation::Latest
+    }
+}
+
+impl Display for ShelterAggregation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let s = to_plain_string(self).map_err(|_| FmtError)?;
+        s.fmt(f)
+    }
+}
+
+impl FromStr for ShelterAggregation {
+    type Err = SerdePlainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_plain_str(s)
+    }
+}
+
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct GetShelterRequest {
     pub shelter_id: Uuid,
@@ -93,6 +303,17 @@ pub struct GetShelterResponse {
     pub shelter: Option<Shelter>,
 }
 
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct GetSheltersRequest {
+    pub shelter_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetSheltersResponse {
+    /// Shelters that were found, in no particular order.
+    pub shelters: Vec<Shelter>,
+}
+
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct GetShelterBySlugRequest {
     pub slug: Slug,
@@ -106,6 +327,7 @@ pub struct GetShelterBySlugResponse {
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct GetShelterSignalsRequest {
     pub shelter_id: Uuid,
+    pub include_archived: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,7 +336,81 @@ pub struct GetShelterSignalsResponse {
 }
 
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct GetSheltersSignalsRequest {
+    pub shelter_ids: Vec<Uuid>,
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetSheltersSignalsResponse {
+    /// Signals belonging to any of the shelters, in no particular order.
+    pub signals: Vec<Signal>,
+}
+
+/// A `ShelterFilter` restricts which shelters are listed.
+///
+/// Empty sets and `None`s match all shelters.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShelterFilter {
+    /// Only match shelters with all of these tags.
+    pub tags: Set<ShelterTag>,
+
+    /// Only match shelters with any of these food options.
+    pub food: Set<ShelterFood>,
+
+    /// Only match shelters in this city (case-insensitive).
+    pub city: Option<String>,
+
+    /// Only match shelters in this region (case-insensitive).
+    pub region: Option<String>,
+
+    /// Only match shelters with this kind of free space.
+    pub vacancy: Option<ShelterVacancy>,
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShelterVacancy {
+    /// A free spot or a free bed.
+    Any,
+    Spots,
+    Beds,
+}
+
+#[derive(Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+pub struct ShelterSort {
+    pub field: ShelterSortField,
+    pub direction: SortDirection,
+}
+
+impl Default for ShelterSort {
+    fn default() -> Self {
+        Self {
+            field: ShelterSortField::Name,
+            direction: SortDirection::Ascending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShelterSortField {
+    Name,
+
+    /// The total number of free spots and beds.
+    Availability,
+
+    UpdatedAt,
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListSheltersRequest {
+    pub filter: ShelterFilter,
+    pub sort: ShelterSort,
     pub limit: u32,
     pub offset: u32,
 }
@@ -124,6 +420,38 @@ pub struct ListSheltersResponse {
     pub shelters: Vec<Shelter>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountSheltersRequest {
+    pub filter: ShelterFilter,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountSheltersResponse {
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListSheltersNearRequest {
+    pub coordinate: Coordinate,
+    pub radius_km: f64,
+    pub only_available: bool,
+    pub limit: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListSheltersNearResponse {
+    /// Nearby shelters, sorted by increasing distance.
+    pub shelters: Vec<NearbyShelter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearbyShelter {
+    pub shelter: Shelter,
+
+    /// The distance to the shelter, in kilometers.
+    pub distance: f64,
+}
+
 // #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 // pub struct ListShelterMeasurementsRequest {
 //     pub shelter_id: Uuid,
@@ -168,8 +496,17 @@ pub struct UpdateShelterRequest {
     pub address: Option<Address>,
     pub location: Option<Coordinate>,
     pub capacity: Option<ShelterSpace>,
+
+    /// How to handle future readings that exceed the shelter's capacity.
+    pub capacity_policy: Option<ShelterCapacityPolicy>,
+
     pub food: Option<ShelterFood>,
     pub tags: Option<Set<ShelterTag>>,
+
+    /// How to compute the shelter's occupancy. Aggregations other than
+    /// `ShelterAggregation::Primary` clear the shelter's primary signal.
+    pub aggregation: Option<ShelterAggregation>,
+    pub primary_signal_id: Option<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -177,6 +514,18 @@ pub struct UpdateShelterResponse {
     pub shelter: Shelter,
 }
 
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct UpdateShelterOccupancyRequest {
+    pub shelter_id: Uuid,
+    pub occupancy: ShelterSpace,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateShelterOccupancyResponse {
+    pub shelter: Shelter,
+    pub measurement: ShelterMeasurement,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeleteShelterRequest {
     pub shelter_id: Uuid,
@@ -185,6 +534,96 @@ pub struct DeleteShelterRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeleteShelterResponse {}
 
+/// A `ShelterOccupancyEvent` is published whenever a `Shelter`'s occupancy is
+/// updated by a new measurement.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShelterOccupancyEvent {
+    pub shelter: Shelter,
+    pub measurement: ShelterMeasurement,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct WatchShelterOccupancyRequest {
+    /// If set, only events for this shelter are delivered.
+    pub shelter_id: Option<Uuid>,
+}
+
+pub struct WatchShelterOccupancyResponse {
+    pub events: BoxStream<'static, ShelterOccupancyEvent>,
+}
+
+fn filter_shelters(
+    query: SheltersQuery<'static, Pg>,
+    filter: ShelterFilter,
+) -> SheltersQuery<'static, Pg> {
+    use schema::shelters;
+
+    let ShelterFilter {
+        tags,
+        food,
+        city,
+        region,
+        vacancy,
+    } = filter;
+
+    let mut query = query;
+    if !tags.is_empty() {
+        let tags: Vec<_> = tags.iter().map(ToString::to_string).collect();
+        query = query.filter(shelters::tags.contains(tags));
+    }
+    if !food.is_empty() {
+        let food: Vec<_> = food.iter().map(ToString::to_string).collect();
+        query = query.filter(shelters::food.eq_any(food));
+    }
+    if let Some(city) = city {
+        let city_field = jsonb_extract_path_text(shelters::address, "city");
+        query = query.filter(lower(city_field).eq(city.to_lowercase()));
+    }
+    if let Some(region) = region {
+        let region_field = jsonb_extract_path_text(shelters::address, "region");
+        query = query.filter(lower(region_field).eq(region.to_lowercase()));
+    }
+    if let Some(vacancy) = vacancy {
+        // Shelters with unknown occupancy are treated as empty.
+        let free_spots =
+            coalesce(shelters::occupied_spots, 0).lt(shelters::total_spots);
+        let free_beds =
+            coalesce(shelters::occupied_beds, 0).lt(shelters::total_beds);
+        query = match vacancy {
+            ShelterVacancy::Any => query.filter(free_spots.or(free_beds)),
+            ShelterVacancy::Spots => query.filter(free_spots),
+            ShelterVacancy::Beds => query.filter(free_beds),
+        };
+    }
+    query
+}
+
+fn sort_shelters(
+    query: SheltersQuery<'static, Pg>,
+    sort: ShelterSort,
+) -> SheltersQuery<'static, Pg> {
+    use schema::shelters;
+
+    let ShelterSort { field, direction } = sort;
+    let free_space = (shelters::total_spots
+        - coalesce(shelters::occupied_spots, 0))
+        + (shelters::total_beds - coalesce(shelters::occupied_beds, 0));
+
+    use ShelterSortField::*;
+    use SortDirection::*;
+    let query = match (field, direction) {
+        (Name, Ascending) => query.order(shelters::name.asc()),
+        (Name, Descending) => query.order(shelters::name.desc()),
+        (Availability, Ascending) => query.order(free_space.asc()),
+        (Availability, Descending) => query.order(free_space.desc()),
+        (UpdatedAt, Ascending) => query.order(shelters::updated_at.asc()),
+        (UpdatedAt, Descending) => query.order(shelters::updated_at.desc()),
+    };
+
+    // Break ties deterministically, so that pagination is stable.
+    query.then_order_by(shelters::id.asc())
+}
+
 impl Service {
     pub(super) async fn can_list_shelters(
         &self,
@@ -206,14 +645,43 @@ impl Service {
     pub(super) async fn can_edit_shelter(
         &self,
         context: &Context,
-        _shelter_id: Uuid,
+        shelter_id: Uuid,
     ) -> Result<bool> {
-        if context.is_internal() {
+        if context.is_privileged() {
             return Ok(true);
         }
 
-        // Restrict shelter editing.
-        Ok(false)
+        // Shelter managers can edit.
+        self.has_shelter_role(context, shelter_id, ShelterRole::Manager)
+            .await
+    }
+
+    pub(super) async fn can_report_shelter_occupancy(
+        &self,
+        context: &Context,
+        shelter_id: Uuid,
+    ) -> Result<bool> {
+        if context.is_privileged() {
+            return Ok(true);
+        }
+
+        // Shelter staff can report occupancy.
+        self.has_shelter_role(context, shelter_id, ShelterRole::Staff)
+            .await
+    }
+
+    pub(super) async fn can_delete_shelter(
+        &self,
+        context: &Context,
+        shelter_id: Uuid,
+    ) -> Result<bool> {
+        if context.is_privileged() {
+            return Ok(true);
+        }
+
+        // Shelter owners can delete.
+        self.has_shelter_role(context, shelter_id, ShelterRole::Owner)
+            .await
     }
 }
 
@@ -257,6 +725,45 @@ impl Service {
         Ok(response)
     }
 
+    pub async fn get_shelters(
+        &self,
+        context: &Context,
+        request: GetSheltersRequest,
+    ) -> Result<GetSheltersResponse> {
+        let GetSheltersRequest { shelter_ids } = request;
+
+        let shelters = {
+            let pool = self.db_pool.clone();
+            let models =
+                spawn_blocking(move || -> Result<Vec<ShelterModel>> {
+                    use schema::shelters;
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    shelters::table
+                        .filter(shelters::id.eq_any(shelter_ids))
+                        .load(&conn)
+                        .context("failed to load shelter models")
+                })
+                .await
+                .unwrap()?;
+            models
+                .into_iter()
+                .map(Shelter::try_from)
+                .collect::<Result<Vec<_>>>()
+                .context("failed to decode shelter models")?
+        };
+
+        // Assert shelters are viewable.
+        for shelter in &shelters {
+            if !self.can_view_shelter(context, shelter.id).await? {
+                bail!("not authorized");
+            }
+        }
+
+        let response = GetSheltersResponse { shelters };
+        Ok(response)
+    }
+
     pub async fn get_shelter_by_slug(
         &self,
         context: &Context,
@@ -302,7 +809,10 @@ impl Service {
         context: &Context,
         request: GetShelterSignalsRequest,
     ) -> Result<GetShelterSignalsResponse> {
-        let GetShelterSignalsRequest { shelter_id } = request;
+        let GetShelterSignalsRequest {
+            shelter_id,
+            include_archived,
+        } = request;
 
         // Assert shelter is viewable.
         if !self.can_view_shelter(context, shelter_id).await? {
@@ -314,10 +824,13 @@ impl Service {
             let models = spawn_blocking(move || -> Result<Vec<SignalModel>> {
                 use schema::signals;
                 let conn = pool.get().context("database connection failure")?;
-                signals::table
+                let mut query = signals::table
                     .filter(signals::shelter_id.eq(shelter_id))
-                    .load(&conn)
-                    .context("failed to load signal model")
+                    .into_boxed();
+                if !include_archived {
+                    query = query.filter(signals::archived_at.is_null());
+                }
+                query.load(&conn).context("failed to load signal model")
             })
             .await
             .unwrap()?;
@@ -332,12 +845,60 @@ impl Service {
         Ok(response)
     }
 
+    pub async fn get_shelters_signals(
+        &self,
+        context: &Context,
+        request: GetSheltersSignalsRequest,
+    ) -> Result<GetSheltersSignalsResponse> {
+        let GetSheltersSignalsRequest {
+            shelter_ids,
+            include_archived,
+        } = request;
+
+        // Assert shelters are viewable.
+        for shelter_id in &shelter_ids {
+            if !self.can_view_shelter(context, *shelter_id).await? {
+                bail!("not authorized");
+            }
+        }
+
+        let signals = {
+            let pool = self.db_pool.clone();
+            let models = spawn_blocking(move || -> Result<Vec<SignalModel>> {
+                use schema::signals;
+                let conn = pool.get().context("database connection failure")?;
+                let mut query = signals::table
+                    .filter(signals::shelter_id.eq_any(shelter_ids))
+                    .into_boxed();
+                if !include_archived {
+                    query = query.filter(signals::archived_at.is_null());
+                }
+                query.load(&conn).context("failed to load signal models")
+            })
+            .await
+            .unwrap()?;
+            models
+                .into_iter()
+                .map(Signal::try_from)
+                .collect::<Result<Vec<_>>>()
+                .context("failed to decode signal models")?
+        };
+
+        let response = GetSheltersSignalsResponse { signals };
+        Ok(response)
+    }
+
     pub async fn list_shelters(
         &self,
         context: &Context,
         request: ListSheltersRequest,
     ) -> Result<ListSheltersResponse> {
-        let ListSheltersRequest { limit, offset } = request;
+        let ListSheltersRequest {
+            filter,
+            sort,
+            limit,
+            offset,
+        } = request;
 
         // Assert shelter is viewable.
         if !self.can_list_shelters(context).await? {
@@ -351,7 +912,10 @@ impl Service {
                     use schema::shelters;
                     let conn =
                         pool.get().context("database connection failure")?;
-                    shelters::table
+                    let query = shelters::table.into_boxed();
+                    let query = filter_shelters(query, filter);
+                    let query = sort_shelters(query, sort);
+                    query
                         .limit(limit.into())
                         .offset(offset.into())
                         .load(&conn)
@@ -370,6 +934,136 @@ impl Service {
         Ok(response)
     }
 
+    pub async fn count_shelters(
+        &self,
+        context: &Context,
+        request: CountSheltersRequest,
+    ) -> Result<CountSheltersResponse> {
+        let CountSheltersRequest { filter } = request;
+
+        // Assert shelters are listable.
+        if !self.can_list_shelters(context).await? {
+            bail!("not authorized");
+        }
+
+        let count = {
+            let pool = self.db_pool.clone();
+            let count = spawn_blocking(move || -> Result<i64> {
+                use schema::shelters;
+                let conn = pool.get().context("database connection failure")?;
+                let query = shelters::table.into_boxed();
+                let query = filter_shelters(query, filter);
+                query
+                    .count()
+                    .get_result(&conn)
+                    .context("failed to count shelters")
+            })
+            .await
+            .unwrap()?;
+            count.try_into().context("invalid shelter count")?
+        };
+
+        let response = CountSheltersResponse { count };
+        Ok(response)
+    }
+
+    pub async fn list_shelters_near(
+        &self,
+        context: &Context,
+        request: ListSheltersNearRequest,
+    ) -> Result<ListSheltersNearResponse> {
+        let ListSheltersNearRequest {
+            coordinate,
+            radius_km,
+            only_available,
+            limit,
+        } = request;
+
+        // Validate search radius.
+        if !(radius_km > 0.0 && radius_km <= MAX_SEARCH_RADIUS_KM) {
+            bail!("radius must be between 0 and {}km", MAX_SEARCH_RADIUS_KM);
+        }
+
+        // Assert shelters are listable.
+        if !self.can_list_shelters(context).await? {
+            bail!("not authorized");
+        }
+
+        let Coordinate { x, y } = coordinate;
+        let origin = Point::new(f64::from(x), f64::from(y));
+
+        // Load shelters within the bounding box of the search radius.
+        //
+        // The box is computed by the `earthdistance` extension in three
+        // dimensions, so it doesn't need to be split at the antimeridian
+        // or near the poles, and is matched against the GiST index on
+        // shelter coordinates.
+        let shelters = {
+            let pool = self.db_pool.clone();
+            let (latitude, longitude) = (origin.y(), origin.x());
+            let radius_m = radius_km * 1000.0 * SEARCH_RADIUS_MARGIN;
+            let models =
+                spawn_blocking(move || -> Result<Vec<ShelterModel>> {
+                    use schema::shelters;
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    let filter = ShelterFilter {
+                        vacancy: if only_available {
+                            Some(ShelterVacancy::Any)
+                        } else {
+                            None
+                        },
+                        ..Default::default()
+                    };
+                    let query = shelters::table.into_boxed();
+                    filter_shelters(query, filter)
+                        .filter(CubeContains::new(
+                            earth_box(
+                                ll_to_earth(latitude, longitude),
+                                radius_m,
+                            ),
+                            ll_to_earth(
+                                shelters::latitude,
+                                shelters::longitude,
+                            ),
+                        ))
+                        .load(&conn)
+                        .context("failed to load shelter models")
+                })
+                .await
+                .unwrap()?;
+            models
+                .into_iter()
+                .map(Shelter::try_from)
+                .collect::<Result<Vec<_>>>()
+                .context("failed to decode shelter models")?
+        };
+
+        // Measure distances, and discard shelters outside the search radius.
+        let mut shelters = shelters
+            .into_iter()
+            .filter_map(|shelter| {
+                let Coordinate { x, y } = shelter.location;
+                let location = Point::new(f64::from(x), f64::from(y));
+                let distance = origin.haversine_distance(&location) / 1000.0;
+                if distance <= radius_km {
+                    Some(NearbyShelter { shelter, distance })
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        shelters.sort_by(|a, b| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .expect("distances should be finite")
+        });
+        shelters.truncate(limit.try_into().unwrap());
+
+        let response = ListSheltersNearResponse { shelters };
+        Ok(response)
+    }
+
     // pub async fn list_shelter_measurements(
     //     &self,
     //     context: &Context,
@@ -415,6 +1109,34 @@ impl Service {
     //     Ok(response)
     // }
 
+    pub async fn watch_shelter_occupancy(
+        &self,
+        context: &Context,
+        request: WatchShelterOccupancyRequest,
+    ) -> Result<WatchShelterOccupancyResponse> {
+        let WatchShelterOccupancyRequest { shelter_id } = request;
+
+        // Assert shelter(s) are viewable.
+        if let Some(shelter_id) = shelter_id {
+            if !self.can_view_shelter(context, shelter_id).await? {
+                bail!("not authorized");
+            }
+        } else if !self.can_list_shelters(context).await? {
+            bail!("not authorized");
+        }
+
+        let events = self.occupancy_bus.subscribe();
+        let events = match shelter_id {
+            Some(shelter_id) => events
+                .filter(move |event| ready(event.shelter.id == shelter_id))
+                .boxed(),
+            None => events,
+        };
+
+        let response = WatchShelterOccupancyResponse { events };
+        Ok(response)
+    }
+
     pub async fn create_shelter(
         &self,
         context: &Context,
@@ -435,7 +1157,7 @@ impl Service {
         } = request;
 
         // Restrict shelter creation.
-        if !context.is_internal() {
+        if !context.is_privileged() {
             bail!("not authorized");
         }
 
@@ -465,7 +1187,11 @@ impl Service {
                 location,
 
                 capacity,
+                capacity_policy: Default::default(),
                 occupancy: None,
+                occupancy_updated_at: None,
+                aggregation: Default::default(),
+                primary_signal_id: None,
                 food,
                 tags,
             }
@@ -509,8 +1235,11 @@ impl Service {
             address,
             location,
             capacity,
+            capacity_policy,
             food,
             tags,
+            aggregation,
+            primary_signal_id,
         } = request;
 
         // Assert shelter is editable.
@@ -557,26 +1286,86 @@ impl Service {
         if let Some(space) = capacity {
             shelter.capacity = space
         }
+        if let Some(policy) = capacity_policy {
+            shelter.capacity_policy = policy;
+        }
         if let Some(food) = food {
             shelter.food = food;
         }
         if let Some(tags) = tags {
             shelter.tags = tags;
         }
-
-        // Update shelter in database.
+        let is_reaggregated =
+            aggregation.is_some() || primary_signal_id.is_some();
+        if let Some(aggregation) = aggregation {
+            shelter.aggregation = aggregation;
+            if aggregation != ShelterAggregation::Primary {
+                shelter.primary_signal_id = None;
+            }
+        }
+        if let Some(signal_id) = primary_signal_id {
+            let signal_shelter_id = self
+                .internal_get_signal_shelter_id(signal_id)
+                .await
+                .context("failed to get primary signal")?;
+            if signal_shelter_id != shelter_id {
+                bail!("primary signal belongs to another shelter")
+            }
+            shelter.primary_signal_id = Some(signal_id);
+        }
+        if shelter.aggregation == ShelterAggregation::Primary
+            && shelter.primary_signal_id.is_none()
         {
+            bail!("missing primary signal")
+        }
+        shelter.updated_at = Utc::now();
+
+        // Update shelter in database, keeping any occupancy that was measured
+        // since the shelter was fetched.
+        let shelter = {
             let pool = self.db_pool.clone();
-            let shelter = ShelterModel::try_from(shelter.clone())
+            let shelter = ShelterModel::try_from(shelter)
                 .context("failed to encode shelter")?;
-            spawn_blocking(move || -> Result<()> {
+            spawn_blocking(move || -> Result<Shelter> {
                 use schema::shelters;
                 let conn = pool.get().context("database connection failure")?;
-                update(shelters::table.find(shelter_id))
-                    .set(shelter)
-                    .execute(&conn)
-                    .context("failed to update shelter model")?;
-                Ok(())
+                conn.transaction(|| {
+                    let (occupied_spots, occupied_beds, occupancy_updated_at) =
+                        shelters::table
+                            .find(shelter_id)
+                            .select((
+                                shelters::occupied_spots,
+                                shelters::occupied_beds,
+                                shelters::occupancy_updated_at,
+                            ))
+                            .for_update()
+                            .first(&conn)
+                            .context("failed to load shelter occupancy")?;
+                    let shelter = ShelterModel {
+                        occupied_spots,
+                        occupied_beds,
+                        occupancy_updated_at,
+                        ..shelter
+                    };
+                    let mut shelter = Shelter::try_from(shelter)
+                        .context("failed to decode shelter")?;
+
+                    // Recompute occupancy if its aggregation changed.
+                    if is_reaggregated {
+                        let (occupancy, occupancy_updated_at) =
+                            recompute_shelter_occupancy(&conn, &shelter)?;
+                        shelter.occupancy = occupancy;
+                        shelter.occupancy_updated_at = occupancy_updated_at;
+                    }
+
+                    let model = ShelterModel::try_from(shelter.clone())
+                        .context("failed to encode shelter")?;
+                    update(shelters::table.find(shelter_id))
+                        .set(model)
+                        .execute(&conn)
+                        .context("failed to update shelter model")?;
+                    Ok(shelter)
+                })
             })
             .await
             .unwrap()?
@@ -586,6 +1375,134 @@ impl Service {
         Ok(response)
     }
 
+    pub async fn update_shelter_occupancy(
+        &self,
+        context: &Context,
+        request: UpdateShelterOccupancyRequest,
+    ) -> Result<UpdateShelterOccupancyResponse> {
+        let UpdateShelterOccupancyRequest {
+            shelter_id,
+            occupancy,
+        } = request;
+
+        // Assert viewer can report shelter occupancy.
+        if !self
+            .can_report_shelter_occupancy(context, shelter_id)
+            .await?
+        {
+            bail!("not authorized")
+        }
+        let user_id = context
+            .viewing_user()
+            .map(|user| user.id)
+            .context("missing viewing user")?;
+
+        // Update shelter occupancy and create measurement.
+        let (shelter, measurement) = {
+            let Meta {
+                id,
+                created_at,
+                updated_at,
+            } = Meta::new();
+            let pool = self.db_pool.clone();
+            spawn_blocking(move || -> Result<(Shelter, ShelterMeasurement)> {
+                use schema::shelter_measurements as measurements;
+                use schema::shelters;
+                let conn = pool.get().context("database connection failure")?;
+                conn.transaction(|| {
+                    let shelter: ShelterModel = shelters::table
+                        .find(shelter_id)
+                        .select(SHELTER_COLUMNS)
+                        .for_update()
+                        .first(&conn)
+                        .optional()
+                        .context("failed to load shelter model")?
+                        .context("shelter not found")?;
+                    let mut shelter = Shelter::try_from(shelter)
+                        .context("failed to decode shelter model")?;
+
+                    // Validate occupancy against the shelter's capacity.
+                    let (occupancy, status) = shelter
+                        .capacity_policy
+                        .apply_space(&occupancy, &shelter.capacity)?;
+                    let ShelterSpace { spots, beds } = occupancy;
+
+                    // Update shelter occupancy, unless it was flagged.
+                    let occupancy = if status
+                        == ShelterMeasurementStatus::Flagged
+                    {
+                        shelter.occupancy.clone().unwrap_or_default()
+                    } else {
+                        shelter.occupancy = Some(ShelterSpace { spots, beds });
+                        shelter.occupancy_updated_at = Some(created_at);
+                        shelter.updated_at = updated_at;
+                        update(shelters::table.find(shelter_id))
+                            .set((
+                                shelters::occupied_spots.eq(i32::from(spots)),
+                                shelters::occupied_beds.eq(i32::from(beds)),
+                                shelters::occupancy_updated_at.eq(created_at),
+                                shelters::updated_at.eq(updated_at),
+                            ))
+                            .execute(&conn)
+                            .context("failed to update shelter model")?;
+                        ShelterSpace { spots, beds }
+                    };
+
+                    // Create measurement.
+                    let measurement = ShelterMeasurement {
+                        id,
+                        created_at,
+                        updated_at,
+
+                        shelter_id,
+                        signal_id: None,
+                        user_id: Some(user_id),
+
+                        capacity: shelter.capacity.clone(),
+                        occupancy,
+                        value: None,
+                        delta: None,
+                        status,
+
+                        retracted_at: None,
+                        retracted_by_id: None,
+                        retraction_reason: None,
+                        correction_of_id: None,
+
+                        idempotency_key: None,
+                    };
+                    insert_into(measurements::table)
+                        .values(
+                            ShelterMeasurementModel::try_from(
+                                measurement.clone(),
+                            )
+                            .context("failed to encode measurement")?,
+                        )
+                        .execute(&conn)
+                        .context("failed to insert measurement model")?;
+
+                    Ok((shelter, measurement))
+                })
+            })
+            .await
+            .unwrap()?
+        };
+
+        // Notify occupancy watchers, unless the measurement was flagged.
+        if measurement.status != ShelterMeasurementStatus::Flagged {
+            self.occupancy_bus.publish(ShelterOccupancyEvent {
+                shelter: shelter.clone(),
+                measurement: measurement.clone(),
+            });
+        }
+
+        let response = UpdateShelterOccupancyResponse {
+            shelter,
+            measurement,
+        };
+        Ok(response)
+    }
+
     pub async fn delete_shelter(
         &self,
         context: &Context,
@@ -593,8 +1510,8 @@ impl Service {
     ) -> Result<DeleteShelterResponse> {
         let DeleteShelterRequest { shelter_id } = request;
 
-        // Assert shelter is editable.
-        if !self.can_edit_shelter(context, shelter_id).await? {
+        // Assert shelter is deletable.
+        if !self.can_delete_shelter(context, shelter_id).await? {
             bail!("not authorized");
         };
 
@@ -617,3 +1534,177 @@ impl Service {
         Ok(response)
     }
 }
+
+/// A `SignalReading` is a signal's own reading at a point in time.
+#[derive(Debug, Clone)]
+pub(super) struct SignalReading {
+    pub signal_id: Uuid,
+    pub created_at: DateTime,
+    pub value: u16,
+}
+
+/// Load the latest reading of each of a shelter's active signals that
+/// measure the given part of its occupancy, ignoring flagged and retracted
+/// readings.
+pub(super) fn load_signal_readings(
+    conn: &PgConnection,
+    shelter_id: Uuid,
+    measure: &ShelterMeasure,
+) -> Result<Vec<SignalReading>> {
+    load_signal_readings_before(conn, shelter_id, measure, None)
+}
+
+/// Like `load_signal_readings`, but only considers readings made before the
+/// given time, if any.
+pub(super) fn load_signal_readings_before(
+    conn: &PgConnection,
+    shelter_id: Uuid,
+    measure: &ShelterMeasure,
+    before: Option<DateTime>,
+) -> Result<Vec<SignalReading>> {
+    use schema::shelter_measurements as measurements;
+    use schema::signals;
+
+    let mut query = measurements::table
+        .inner_join(signals::table)
+        .filter(signals::shelter_id.eq(shelter_id))
+        .filter(signals::measure.eq(measure.to_string()))
+        .filter(signals::archived_at.is_null())
+        .filter(measurements::retracted_at.is_null())
+        .filter(
+            measurements::status
+                .ne(ShelterMeasurementStatus::Flagged.to_string()),
+        )
+        .distinct_on(measurements::signal_id)
+        .order((
+            measurements::signal_id,
+            measurements::created_at.desc(),
+            measurements::id.desc(),
+        ))
+        .select((
+            measurements::signal_id,
+            measurements::created_at,
+            measurements::value,
+        ))
+        .into_boxed();
+    if let Some(before) = before {
+        query = query.filter(measurements::created_at.lt(before));
+    }
+    let readings: Vec<(Option<Uuid>, DateTime, Option<i32>)> =
+        query.load(conn).context("failed to load signal readings")?;
+    readings
+        .into_iter()
+        .map(|(signal_id, created_at, value)| {
+            let signal_id = signal_id.context("missing signal")?;
+            let value = value
+                .context("missing reading")?
+                .try_into()
+                .context("invalid reading")?;
+            Ok(SignalReading {
+                signal_id,
+                created_at,
+                value,
+            })
+        })
+        .collect()
+}
+
+/// Aggregate a shelter's occupancy from the readings of its signals, or
+/// `None` if there are no readings.
+pub(super) fn aggregate_signal_readings(
+    shelter: &Shelter,
+    readings: &[SignalReading],
+) -> Option<u16> {
+    let values = readings.iter().map(|reading| reading.value);
+    let latest = readings
+        .iter()
+        .max_by_key(|reading| reading.created_at)
+        .map(|reading| reading.value);
+    match shelter.aggregation {
+        ShelterAggregation::Latest => latest,
+        ShelterAggregation::Sum if readings.is_empty() => None,
+        ShelterAggregation::Sum => {
+            Some(values.fold(0, |sum: u16, value| sum.saturating_add(value)))
+        }
+        ShelterAggregation::Max => values.max(),
+        ShelterAggregation::Primary => readings
+            .iter()
+            .find(|reading| {
+                Some(reading.signal_id) == shelter.primary_signal_id
+            })
+            .map(|reading| reading.value)
+            .or(latest),
+    }
+}
+
+/// Aggregate a shelter's occupancy for a measure from the latest reading of
+/// each of its active signals, or `None` if they have no readings.
+///
+/// The occupancy is clamped to the shelter's capacity, regardless of its
+/// capacity policy.
+pub(super) fn aggregate_shelter_occupancy(
+    conn: &PgConnection,
+    shelter: &Shelter,
+    measure: &ShelterMeasure,
+) -> Result<Option<u16>> {
+    let readings = load_signal_readings(conn, shelter.id, measure)?;
+    let capacity = measure.get(&shelter.capacity);
+    let aggregate = aggregate_signal_readings(shelter, &readings);
+    Ok(aggregate.map(|aggregate| aggregate.min(capacity)))
+}
+
+/// Recompute a shelter's occupancy from its measurement history, ignoring
+/// flagged and retracted measurements, along with the time of the latest
+/// measurement that it reflects.
+///
+/// Signals' readings are aggregated as they would have been when reported,
+/// except where a measurement reported by hand is newer, and clamped to the
+/// shelter's capacity.
+pub(super) fn recompute_shelter_occupancy(
+    conn: &PgConnection,
+    shelter: &Shelter,
+) -> Result<(Option<ShelterSpace>, Option<DateTime>)> {
+    use schema::shelter_measurements as measurements;
+
+    // Find the latest occupancy reported by hand.
+    let reported: Option<ShelterMeasurementModel> = measurements::table
+        .filter(measurements::shelter_id.eq(shelter.id))
+        .filter(measurements::user_id.is_not_null())
+        .filter(measurements::retracted_at.is_null())
+        .filter(
+            measurements::status
+                .ne(ShelterMeasurementStatus::Flagged.to_string()),
+        )
+        .order((measurements::created_at.desc(), measurements::id.desc()))
+        .first(conn)
+        .optional()
+        .context("failed to load latest reported measurement")?;
+    let reported = reported
+        .map(ShelterMeasurement::try_from)
+        .transpose()
+        .context("failed to decode measurement")?;
+    let reported_at = reported.as_ref().map(|reported| reported.created_at);
+    let mut occupancy = reported.map(|reported| reported.occupancy);
+    let mut updated_at = reported_at;
+
+    // Aggregate signals' readings where they're newer.
+    for measure in &[ShelterMeasure::Spots, ShelterMeasure::Beds] {
+        let readings = load_signal_readings(conn, shelter.id, measure)?;
+        let latest = readings.iter().map(|reading| reading.created_at).max();
+        let latest = match latest {
+            Some(latest) => latest,
+            None => continue,
+        };
+        if matches!(reported_at, Some(reported_at) if reported_at >= latest) {
+            continue;
+        }
+        if let Some(value) = aggregate_signal_readings(shelter, &readings) {
+            let value = value.min(measure.get(&shelter.capacity));
+            let space = occupancy.unwrap_or_default();
+            occupancy = Some(measure.replace(space, value));
+            updated_at = updated_at.max(Some(latest));
+        }
+    }
+
+    Ok((occupancy, updated_at))
+}
@@ -1,7 +1,13 @@
 use super::prelude::*;
 
+use models::Shelter as ShelterModel;
 use models::ShelterMeasurement as ShelterMeasurementModel;
 
+use models::SHELTER_COLUMNS;
+
+use diesel::pg::Pg;
+use schema::shelter_measurements::BoxedQuery as ShelterMeasurementsQuery;
+
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct ShelterMeasurement {
     pub id: Uuid,
@@ -9,16 +15,341 @@ pub struct ShelterMeasurement {
     pub updated_at: DateTime,
 
     pub shelter_id: Uuid,
-    pub signal_id: Uuid,
+
+    /// The signal that reported this measurement, unless it was reported by
+    /// a user.
+    pub signal_id: Option<Uuid>,
+
+    /// The user that reported this measurement by hand, unless it was
+    /// reported by a signal.
+    pub user_id: Option<Uuid>,
 
     pub capacity: ShelterSpace,
     pub occupancy: ShelterSpace,
+
+    /// The signal's own reading, which the shelter's occupancy is aggregated
+    /// from (see `ShelterAggregation`), if reported by a signal.
+    pub value: Option<u16>,
+
+    /// The change that the signal reported, if it reported a delta rather
+    /// than an absolute reading.
+    pub delta: Option<i32>,
+
+    /// Whether the reading was accepted as-is, or adjusted by the shelter's
+    /// `ShelterCapacityPolicy`.
+    pub status: ShelterMeasurementStatus,
+
+    /// When an admin retracted this measurement, after which it no longer
+    /// affects its shelter's occupancy.
+    pub retracted_at: Option<DateTime>,
+    pub retracted_by_id: Option<Uuid>,
+    pub retraction_reason: Option<String>,
+
+    /// The retracted measurement that this measurement replaces.
+    pub correction_of_id: Option<Uuid>,
+
+    /// A key given by the signal that reported this measurement, which
+    /// identifies retries of the same report.
+    pub idempotency_key: Option<String>,
+}
+
+/// A `ShelterMeasurementStatus` describes how a signal's reading was
+/// validated against its shelter's capacity.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShelterMeasurementStatus {
+    /// The reading was within the shelter's capacity.
+    Accepted,
+
+    /// The reading, or the occupancy it was aggregated into, was outside the
+    /// shelter's capacity, and was clamped to it.
+    Clamped,
+
+    /// The reading exceeded the shelter's capacity, and was kept as-is, but
+    /// doesn't affect the shelter's occupancy.
+    Flagged,
+}
+
+impl Display for ShelterMeasurementStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let s = to_plain_string(self).map_err(|_| FmtError)?;
+        s.fmt(f)
+    }
+}
+
+impl FromStr for ShelterMeasurementStatus {
+    type Err = SerdePlainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_plain_str(s)
+    }
+}
+
+/// A position within a measurement history, which is ordered by
+/// `(created_at, id)`.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShelterMeasurementKey {
+    pub created_at: DateTime,
+    pub id: Uuid,
+}
+
+impl From<&ShelterMeasurement> for ShelterMeasurementKey {
+    fn from(measurement: &ShelterMeasurement) -> Self {
+        let ShelterMeasurement { created_at, id, .. } = measurement;
+        Self {
+            created_at: *created_at,
+            id: *id,
+        }
+    }
+}
+
+impl From<(DateTime, Uuid)> for ShelterMeasurementKey {
+    fn from(key: (DateTime, Uuid)) -> Self {
+        let (created_at, id) = key;
+        Self { created_at, id }
+    }
+}
+
+#[derive(Debug, Clone, Default, Hash, Serialize, Deserialize)]
+pub struct ShelterMeasurementFilter {
+    /// Only include measurements created at or after this time.
+    pub since: Option<DateTime>,
+
+    /// Only include measurements created before this time.
+    pub until: Option<DateTime>,
+
+    /// Whether to include measurements that were retracted.
+    pub include_retracted: bool,
 }
 
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 struct ShelterMeasurementRelations {
     shelter_id: Uuid,
-    signal_id: Uuid,
+    signal_id: Option<Uuid>,
+}
+
+pub(super) fn filter_shelter_measurements(
+    query: ShelterMeasurementsQuery<'static, Pg>,
+    filter: ShelterMeasurementFilter,
+) -> ShelterMeasurementsQuery<'static, Pg> {
+    use schema::shelter_measurements as measurements;
+
+    let ShelterMeasurementFilter {
+        since,
+        until,
+        include_retracted,
+    } = filter;
+
+    let mut query = query;
+    if !include_retracted {
+        query = query.filter(measurements::retracted_at.is_null());
+    }
+    if let Some(since) = since {
+        query = query.filter(measurements::created_at.ge(since));
+    }
+    if let Some(until) = until {
+        query = query.filter(measurements::created_at.lt(until));
+    }
+    query
+}
+
+/// Seek to the measurements between `after` and `before`, ordered from the
+/// start (newest) or end (oldest) of the history.
+///
+/// One extra measurement is selected, to determine whether there are more.
+pub(super) fn seek_shelter_measurements(
+    query: ShelterMeasurementsQuery<'static, Pg>,
+    after: Option<ShelterMeasurementKey>,
+    before: Option<ShelterMeasurementKey>,
+    limit: u32,
+    from_end: bool,
+) -> ShelterMeasurementsQuery<'static, Pg> {
+    use schema::shelter_measurements as measurements;
+
+    let mut query = query;
+    if let Some(ShelterMeasurementKey { created_at, id }) = after {
+        let older = measurements::created_at.lt(created_at).or(
+            measurements::created_at
+                .eq(created_at)
+                .and(measurements::id.lt(id)),
+        );
+        query = query.filter(older);
+    }
+    if let Some(ShelterMeasurementKey { created_at, id }) = before {
+        let newer = measurements::created_at.gt(created_at).or(
+            measurements::created_at
+                .eq(created_at)
+                .and(measurements::id.gt(id)),
+        );
+        query = query.filter(newer);
+    }
+
+    let query = if from_end {
+        query.order((measurements::created_at.asc(), measurements::id.asc()))
+    } else {
+        query.order((measurements::created_at.desc(), measurements::id.desc()))
+    };
+    query.limit(i64::from(limit) + 1)
+}
+
+/// Replay the measurements of a shelter that were reported by its signals
+/// after the given time, rewriting their readings and occupancy to account
+/// for an earlier measurement having been retracted or corrected.
+///
+/// Deltas are reapplied to what they adjusted: the shelter's occupancy under
+/// the latest aggregation, and otherwise their signal's previous reading. A
+/// delta that was accepted when it was reported is clamped to the shelter's
+/// capacity, rather than rejected.
+fn replay_shelter_measurements(
+    conn: &PgConnection,
+    shelter: &Shelter,
+    after: DateTime,
+    now: DateTime,
+) -> Result<()> {
+    use schema::shelter_measurements as measurements;
+    use schema::signals;
+    use ShelterMeasurementStatus::*;
+
+    // Start from the shelter's occupancy as of the given time.
+    let previous: Option<ShelterMeasurementModel> = measurements::table
+        .filter(measurements::shelter_id.eq(shelter.id))
+        .filter(measurements::created_at.le(after))
+        .filter(measurements::retracted_at.is_null())
+        .filter(measurements::status.ne(Flagged.to_string()))
+        .order((measurements::created_at.desc(), measurements::id.desc()))
+        .first(conn)
+        .optional()
+        .context("failed to load previous measurement")?;
+    let previous = previous
+        .map(ShelterMeasurement::try_from)
+        .transpose()
+        .context("failed to decode measurement model")?;
+    let mut occupancy = previous
+        .map(|previous| previous.occupancy)
+        .unwrap_or_default();
+
+    // Load later measurements, and what their signals measure.
+    let later: Vec<ShelterMeasurementModel> = measurements::table
+        .filter(measurements::shelter_id.eq(shelter.id))
+        .filter(measurements::created_at.gt(after))
+        .filter(measurements::retracted_at.is_null())
+        .order((measurements::created_at.asc(), measurements::id.asc()))
+        .load(conn)
+        .context("failed to load later measurements")?;
+    let measures: Vec<(Uuid, String)> = signals::table
+        .filter(signals::shelter_id.eq(shelter.id))
+        .select((signals::id, signals::measure))
+        .load(conn)
+        .context("failed to load signal measures")?;
+    let measures = measures
+        .into_iter()
+        .map(|(signal_id, measure)| {
+            let measure = measure
+                .parse::<ShelterMeasure>()
+                .context("failed to parse signal measure")?;
+            Ok((signal_id, measure))
+        })
+        .collect::<Result<Map<_, _>>>()?;
+
+    let policy = match shelter.capacity_policy {
+        ShelterCapacityPolicy::Reject => ShelterCapacityPolicy::Clamp,
+        policy => policy,
+    };
+    for measurement in later {
+        let measurement = ShelterMeasurement::try_from(measurement)
+            .context("failed to decode measurement model")?;
+        let signal_id = match measurement.signal_id {
+            Some(signal_id) => signal_id,
+            None => {
+                // Occupancy reported by hand replaces the shelter's.
+                if measurement.status != Flagged {
+                    occupancy = measurement.occupancy;
+                }
+                continue;
+            }
+        };
+        let measure = measures.get(&signal_id).context("missing signal")?;
+        let capacity = measure.get(&measurement.capacity);
+
+        // Determine the signal's reading, reapplying its delta if any.
+        let mut readings = match shelter.aggregation {
+            ShelterAggregation::Latest => Vec::new(),
+            _ => load_signal_readings_before(
+                conn,
+                shelter.id,
+                measure,
+                Some(measurement.created_at),
+            )?,
+        };
+        let (value, mut status) = match measurement.delta {
+            Some(delta) => {
+                let previous = match shelter.aggregation {
+                    ShelterAggregation::Latest => measure.get(&occupancy),
+                    _ => readings
+                        .iter()
+                        .find(|reading| reading.signal_id == signal_id)
+                        .map(|reading| reading.value)
+                        .unwrap_or_default(),
+                };
+                policy
+                    .apply(i64::from(previous) + i64::from(delta), capacity)?
+            }
+            None => {
+                let value = measurement.value.context("missing reading")?;
+                (value, measurement.status)
+            }
+        };
+
+        // Aggregate the shelter's occupancy with the reading, unless it was
+        // flagged.
+        let base = match shelter.aggregation {
+            ShelterAggregation::Latest => occupancy.clone(),
+            _ => measurement.occupancy.clone(),
+        };
+        let snapshot = if status == Flagged {
+            base
+        } else {
+            readings.retain(|reading| reading.signal_id != signal_id);
+            readings.push(SignalReading {
+                signal_id,
+                created_at: measurement.created_at,
+                value,
+            });
+            let aggregate =
+                aggregate_signal_readings(shelter, &readings).unwrap_or(value);
+            let (aggregate, is_clamped) =
+                policy.apply_aggregate(aggregate, capacity)?;
+            if is_clamped {
+                status = Clamped;
+            }
+            measure.replace(base, aggregate)
+        };
+        if shelter.aggregation == ShelterAggregation::Latest
+            && status != Flagged
+        {
+            occupancy = snapshot.clone();
+        }
+
+        // Rewrite the measurement, if it changed.
+        if measurement.value == Some(value)
+            && measurement.status == status
+            && measurement.occupancy == snapshot
+        {
+            continue;
+        }
+        let ShelterSpace { spots, beds } = snapshot;
+        update(measurements::table.find(measurement.id))
+            .set((
+                measurements::value.eq(i32::from(value)),
+                measurements::status.eq(status.to_string()),
+                measurements::occupied_spots.eq(i32::from(spots)),
+                measurements::occupied_beds.eq(i32::from(beds)),
+                measurements::updated_at.eq(now),
+            ))
+            .execute(conn)
+            .context("failed to update measurement model")?;
+    }
+    Ok(())
 }
 
 impl Service {
@@ -29,7 +360,7 @@ impl Service {
         let relations = {
             let pool = self.db_pool.clone();
             let (shelter_id, signal_id) =
-                spawn_blocking(move || -> Result<(Uuid, Uuid)> {
+                spawn_blocking(move || -> Result<(Uuid, Option<Uuid>)> {
                     use schema::shelter_measurements as measurements;
                     let conn =
                         pool.get().context("database connection failure")?;
@@ -58,7 +389,7 @@ impl Service {
         context: &Context,
         measurement_id: Uuid,
     ) -> Result<bool> {
-        if context.is_internal() {
+        if context.is_privileged() {
             return Ok(true);
         }
 
@@ -76,19 +407,222 @@ impl Service {
         if self.can_view_shelter(context, shelter_id).await? {
             return Ok(true);
         }
-        if self.can_view_signal(context, signal_id).await? {
-            return Ok(true);
+        if let Some(signal_id) = signal_id {
+            if self.can_view_signal(context, signal_id).await? {
+                return Ok(true);
+            }
         }
 
         Ok(false)
     }
 
+    pub(super) async fn can_retract_shelter_measurement(
+        &self,
+        context: &Context,
+    ) -> Result<bool> {
+        // Only admins can retract or correct measurements.
+        Ok(context.is_privileged())
+    }
+
+    /// Retract a measurement, replacing it with a correction if given, and
+    /// replay the later measurements of its shelter and recompute its
+    /// occupancy.
+    ///
+    /// Returns the correction, or else the retracted measurement.
+    async fn internal_retract_shelter_measurement(
+        &self,
+        measurement_id: Uuid,
+        user_id: Option<Uuid>,
+        reason: InputString,
+        correction: Option<ShelterMeasurementCorrection>,
+    ) -> Result<(Shelter, ShelterMeasurement)> {
+        let pool = self.db_pool.clone();
+        spawn_blocking(move || -> Result<(Shelter, ShelterMeasurement)> {
+            use schema::measurement_anomalies as anomalies;
+            use schema::shelter_measurements as measurements;
+            use schema::{shelters, signals};
+            let conn = pool.get().context("database connection failure")?;
+            conn.transaction(|| {
+                let now = Utc::now();
+
+                // Load measurement.
+                let measurement: ShelterMeasurementModel = measurements::table
+                    .find(measurement_id)
+                    .first(&conn)
+                    .optional()
+                    .context("failed to load measurement model")?
+                    .context("measurement not found")?;
+                let mut measurement = ShelterMeasurement::try_from(measurement)
+                    .context("failed to decode measurement model")?;
+
+                // Lock shelter, so that its occupancy isn't changed while it's
+                // recomputed.
+                let shelter: ShelterModel = shelters::table
+                    .find(measurement.shelter_id)
+                    .select(SHELTER_COLUMNS)
+                    .for_update()
+                    .first(&conn)
+                    .context("failed to load shelter model")?;
+                let mut shelter = Shelter::try_from(shelter)
+                    .context("failed to decode shelter model")?;
+
+                // Retract measurement, unless it was already retracted.
+                let retracted = update(
+                    measurements::table
+                        .find(measurement_id)
+                        .filter(measurements::retracted_at.is_null()),
+                )
+                .set((
+                    measurements::retracted_at.eq(now),
+                    measurements::retracted_by_id.eq(user_id),
+                    measurements::retraction_reason.eq(reason.as_str()),
+                    measurements::updated_at.eq(now),
+                ))
+                .execute(&conn)
+                .context("failed to update measurement model")?;
+                if retracted == 0 {
+                    bail!("measurement is already retracted")
+                }
+                measurement.retracted_at = Some(now);
+                measurement.retracted_by_id = user_id;
+                measurement.retraction_reason = Some(reason.into());
+                measurement.updated_at = now;
+
+                // Its anomalies no longer need review.
+                update(
+                    anomalies::table
+                        .filter(anomalies::measurement_id.eq(measurement_id))
+                        .filter(anomalies::resolved_at.is_null()),
+                )
+                .set((
+                    anomalies::resolved_at.eq(now),
+                    anomalies::resolved_by_id.eq(user_id),
+                    anomalies::updated_at.eq(now),
+                ))
+                .execute(&conn)
+                .context("failed to update anomaly models")?;
+
+                // Create correction, in place of the retracted measurement.
+                let correction = match correction {
+                    Some(correction) => {
+                        let policy = shelter.capacity_policy;
+                        let (occupancy, value, status) = match correction {
+                            ShelterMeasurementCorrection::Value(value) => {
+                                let signal_id = measurement.signal_id.context(
+                                    "measurement was reported by a user",
+                                )?;
+                                let measure: String = signals::table
+                                    .find(signal_id)
+                                    .select(signals::measure)
+                                    .first(&conn)
+                                    .context("failed to load signal model")?;
+                                let measure: ShelterMeasure =
+                                    measure.parse().context(
+                                        "failed to parse signal measure",
+                                    )?;
+                                let capacity = measure.get(&shelter.capacity);
+                                let (value, status) =
+                                    policy.apply(value.into(), capacity)?;
+                                let occupancy = measure.replace(
+                                    measurement.occupancy.clone(),
+                                    value,
+                                );
+                                (occupancy, Some(value), status)
+                            }
+                            ShelterMeasurementCorrection::Occupancy(
+                                occupancy,
+                            ) => {
+                                if measurement.user_id.is_none() {
+                                    bail!(
+                                        "measurement was reported by a signal"
+                                    )
+                                }
+                                let (occupancy, status) = policy.apply_space(
+                                    &occupancy,
+                                    &shelter.capacity,
+                                )?;
+                                (occupancy, None, status)
+                            }
+                        };
+                        let Meta { id, .. } = Meta::new();
+                        let correction = ShelterMeasurement {
+                            id,
+                            created_at: measurement.created_at,
+                            updated_at: now,
+
+                            shelter_id: measurement.shelter_id,
+                            signal_id: measurement.signal_id,
+                            user_id: measurement.user_id,
+
+                            capacity: measurement.capacity.clone(),
+                            occupancy,
+                            value,
+                            delta: None,
+                            status,
+
+                            retracted_at: None,
+                            retracted_by_id: None,
+                            retraction_reason: None,
+                            correction_of_id: Some(measurement_id),
+
+                            idempotency_key: None,
+                        };
+                        insert_into(measurements::table)
+                            .values(
+                                ShelterMeasurementModel::try_from(
+                                    correction.clone(),
+                                )
+                                .context("failed to encode measurement")?,
+                            )
+                            .execute(&conn)
+                            .context("failed to insert measurement model")?;
+                        Some(correction)
+                    }
+                    None => None,
+                };
+
+                // Replay the measurements that were reported after it, and
+                // recompute shelter occupancy.
+                replay_shelter_measurements(
+                    &conn,
+                    &shelter,
+                    measurement.created_at,
+                    now,
+                )?;
+                let (occupancy, occupancy_updated_at) =
+                    recompute_shelter_occupancy(&conn, &shelter)?;
+                shelter.occupancy = occupancy;
+                shelter.occupancy_updated_at = occupancy_updated_at;
+                shelter.updated_at = now;
+                let ShelterModel {
+                    occupied_spots,
+                    occupied_beds,
+                    ..
+                } = ShelterModel::try_from(shelter.clone())
+                    .context("failed to encode shelter")?;
+                update(shelters::table.find(shelter.id))
+                    .set((
+                        shelters::occupied_spots.eq(occupied_spots),
+                        shelters::occupied_beds.eq(occupied_beds),
+                        shelters::occupancy_updated_at.eq(occupancy_updated_at),
+                        shelters::updated_at.eq(now),
+                    ))
+                    .execute(&conn)
+                    .context("failed to update shelter model")?;
+
+                Ok((shelter, correction.unwrap_or(measurement)))
+            })
+        })
+        .await
+        .unwrap()
+    }
+
     // pub(super) async fn _can_edit_shelter_measurement(
     //     &self,
     //     context: &Context,
     //     measurement_id: Uuid,
     // ) -> Result<bool> {
-    //     if context.is_internal() {
+    //     if context.is_privileged() {
     //         return Ok(true);
     //     }
 
@@ -164,8 +698,11 @@ impl Service {
         dbg!(&request);
         let ListShelterMeasurementsRequest {
             shelter_id,
+            filter,
+            after,
+            before,
             limit,
-            offset,
+            from_end,
         } = request;
 
         // Assert shelter is viewable.
@@ -174,6 +711,65 @@ impl Service {
         }
 
         // List measurements.
+        let (measurements, has_more) = {
+            let pool = self.db_pool.clone();
+            let mut models = spawn_blocking(
+                move || -> Result<Vec<ShelterMeasurementModel>> {
+                    use schema::shelter_measurements as measurements;
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    let query = measurements::table
+                        .filter(measurements::shelter_id.eq(shelter_id))
+                        .into_boxed();
+                    let query = filter_shelter_measurements(query, filter);
+                    let query = seek_shelter_measurements(
+                        query, after, before, limit, from_end,
+                    );
+                    query
+                        .load(&conn)
+                        .context("failed to load shelter measurement models")
+                },
+            )
+            .await
+            .unwrap()?;
+
+            // Trim the lookahead measurement, and restore newest-first order.
+            let has_more = models.len() > limit as usize;
+            models.truncate(limit as usize);
+            if from_end {
+                models.reverse();
+            }
+
+            let measurements = models
+                .into_iter()
+                .map(ShelterMeasurement::try_from)
+                .collect::<Result<Vec<_>>>()
+                .context("failed to decode shelter measurement models")?;
+            (measurements, has_more)
+        };
+
+        let response = ListShelterMeasurementsResponse {
+            measurements,
+            has_more,
+        };
+        Ok(response)
+    }
+
+    pub async fn get_latest_shelter_measurements(
+        &self,
+        context: &Context,
+        request: GetLatestShelterMeasurementsRequest,
+    ) -> Result<GetLatestShelterMeasurementsResponse> {
+        let GetLatestShelterMeasurementsRequest { shelter_ids } = request;
+
+        // Assert shelters are viewable.
+        for shelter_id in &shelter_ids {
+            if !self.can_view_shelter(context, *shelter_id).await? {
+                bail!("not authorized");
+            }
+        }
+
+        // Load the newest measurement of each shelter.
         let measurements = {
             let pool = self.db_pool.clone();
             let models = spawn_blocking(
@@ -182,10 +778,13 @@ impl Service {
                     let conn =
                         pool.get().context("database connection failure")?;
                     measurements::table
-                        .filter(measurements::shelter_id.eq(shelter_id))
-                        .order(measurements::created_at.desc())
-                        .limit(limit.into())
-                        .offset(offset.into())
+                        .filter(measurements::shelter_id.eq_any(shelter_ids))
+                        .distinct_on(measurements::shelter_id)
+                        .order((
+                            measurements::shelter_id,
+                            measurements::created_at.desc(),
+                            measurements::id.desc(),
+                        ))
                         .load(&conn)
                         .context("failed to load shelter measurement models")
                 },
@@ -199,7 +798,128 @@ impl Service {
                 .context("failed to decode shelter measurement models")?
         };
 
-        let response = ListShelterMeasurementsResponse { measurements };
+        let response = GetLatestShelterMeasurementsResponse { measurements };
+        Ok(response)
+    }
+
+    pub async fn count_shelter_measurements(
+        &self,
+        context: &Context,
+        request: CountShelterMeasurementsRequest,
+    ) -> Result<CountShelterMeasurementsResponse> {
+        let CountShelterMeasurementsRequest { shelter_id, filter } = request;
+
+        // Assert shelter is viewable.
+        if !self.can_view_shelter(context, shelter_id).await? {
+            bail!("not authorized");
+        }
+
+        // Count measurements.
+        let count = {
+            let pool = self.db_pool.clone();
+            let count = spawn_blocking(move || -> Result<i64> {
+                use schema::shelter_measurements as measurements;
+                let conn = pool.get().context("database connection failure")?;
+                let query = measurements::table
+                    .filter(measurements::shelter_id.eq(shelter_id))
+                    .into_boxed();
+                let query = filter_shelter_measurements(query, filter);
+                query
+                    .count()
+                    .get_result(&conn)
+                    .context("failed to count shelter measurements")
+            })
+            .await
+            .unwrap()?;
+            count
+                .try_into()
+                .context("invalid shelter measurement count")?
+        };
+
+        let response = CountShelterMeasurementsResponse { count };
+        Ok(response)
+    }
+
+    pub async fn retract_shelter_measurement(
+        &self,
+        context: &Context,
+        request: RetractShelterMeasurementRequest,
+    ) -> Result<RetractShelterMeasurementResponse> {
+        let RetractShelterMeasurementRequest {
+            measurement_id,
+            reason,
+        } = request;
+
+        if !self.can_retract_shelter_measurement(context).await? {
+            bail!("not authorized")
+        }
+        if reason.is_empty() {
+            bail!("missing reason")
+        }
+
+        // Retract measurement and recompute shelter occupancy.
+        let user_id = context.viewing_user().map(|user| user.id);
+        let (shelter, measurement) = self
+            .internal_retract_shelter_measurement(
+                measurement_id,
+                user_id,
+                reason,
+                None,
+            )
+            .await?;
+
+        // Notify occupancy watchers.
+        self.occupancy_bus.publish(ShelterOccupancyEvent {
+            shelter: shelter.clone(),
+            measurement: measurement.clone(),
+        });
+
+        let response = RetractShelterMeasurementResponse {
+            shelter,
+            measurement,
+        };
+        Ok(response)
+    }
+
+    pub async fn correct_shelter_measurement(
+        &self,
+        context: &Context,
+        request: CorrectShelterMeasurementRequest,
+    ) -> Result<CorrectShelterMeasurementResponse> {
+        let CorrectShelterMeasurementRequest {
+            measurement_id,
+            reason,
+            correction,
+        } = request;
+
+        if !self.can_retract_shelter_measurement(context).await? {
+            bail!("not authorized")
+        }
+        if reason.is_empty() {
+            bail!("missing reason")
+        }
+
+        // Replace measurement and recompute shelter occupancy.
+        let user_id = context.viewing_user().map(|user| user.id);
+        let (shelter, measurement) = self
+            .internal_retract_shelter_measurement(
+                measurement_id,
+                user_id,
+                reason,
+                Some(correction),
+            )
+            .await?;
+
+        // Notify occupancy watchers.
+        self.occupancy_bus.publish(ShelterOccupancyEvent {
+            shelter: shelter.clone(),
+            measurement: measurement.clone(),
+        });
+
+        let response = CorrectShelterMeasurementResponse {
+            shelter,
+            measurement,
+        };
         Ok(response)
     }
 }
@@ -217,11 +937,86 @@ pub struct GetShelterMeasurementResponse {
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct ListShelterMeasurementsRequest {
     pub shelter_id: Uuid,
+    pub filter: ShelterMeasurementFilter,
+
+    /// Only include measurements older than this key.
+    pub after: Option<ShelterMeasurementKey>,
+
+    /// Only include measurements newer than this key.
+    pub before: Option<ShelterMeasurementKey>,
+
     pub limit: u32,
-    pub offset: u32,
+
+    /// If set, select the oldest matching measurements instead of the
+    /// newest.
+    pub from_end: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListShelterMeasurementsResponse {
+    /// Measurements, sorted from newest to oldest.
+    pub measurements: Vec<ShelterMeasurement>,
+
+    /// Whether more measurements match beyond the selected ones.
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct GetLatestShelterMeasurementsRequest {
+    pub shelter_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetLatestShelterMeasurementsResponse {
+    /// The newest measurement of each shelter that has any, in no particular
+    /// order.
     pub measurements: Vec<ShelterMeasurement>,
 }
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct CountShelterMeasurementsRequest {
+    pub shelter_id: Uuid,
+    pub filter: ShelterMeasurementFilter,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountShelterMeasurementsResponse {
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct RetractShelterMeasurementRequest {
+    pub measurement_id: Uuid,
+    pub reason: InputString,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetractShelterMeasurementResponse {
+    pub shelter: Shelter,
+    pub measurement: ShelterMeasurement,
+}
+
+/// A `ShelterMeasurementCorrection` is what a measurement should have been.
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub enum ShelterMeasurementCorrection {
+    /// The reading of the signal that reported the measurement.
+    Value(u16),
+
+    /// The occupancy reported by hand.
+    Occupancy(ShelterSpace),
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct CorrectShelterMeasurementRequest {
+    pub measurement_id: Uuid,
+    pub reason: InputString,
+    pub correction: ShelterMeasurementCorrection,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrectShelterMeasurementResponse {
+    pub shelter: Shelter,
+
+    /// The measurement that replaces the retracted one.
+    pub measurement: ShelterMeasurement,
+}
@@ -0,0 +1,429 @@
+use super::prelude::*;
+
+use models::ShelterMembership as ShelterMembershipModel;
+
+/// A `ShelterMembership` grants a `User` a role within a `Shelter`.
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct ShelterMembership {
+    pub id: Uuid,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+
+    pub shelter_id: Uuid,
+    pub user_id: Uuid,
+    pub role: ShelterRole,
+}
+
+/// A `ShelterRole` determines what a member can do within a `Shelter`.
+///
+/// Roles are ordered, such that each role has all the rights of the roles
+/// before it.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+pub enum ShelterRole {
+    /// Can view the shelter's signals and members.
+    Viewer,
+
+    /// Can report the shelter's occupancy.
+    Staff,
+
+    /// Can edit the shelter and its signals, and manage staff and viewers.
+    Manager,
+
+    /// Can delete the shelter, and manage all of its members.
+    Owner,
+}
+
+impl Display for ShelterRole {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let s = to_plain_string(self).map_err(|_| FmtError)?;
+        s.fmt(f)
+    }
+}
+
+impl FromStr for ShelterRole {
+    type Err = SerdePlainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_plain_str(s)
+    }
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct ListShelterMembershipsRequest {
+    pub shelter_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListShelterMembershipsResponse {
+    pub memberships: Vec<ShelterMembership>,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct GrantShelterMembershipRequest {
+    pub shelter_id: Uuid,
+    pub user_id: Uuid,
+    pub role: ShelterRole,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrantShelterMembershipResponse {
+    pub membership: ShelterMembership,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct RevokeShelterMembershipRequest {
+    pub shelter_id: Uuid,
+    pub user_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokeShelterMembershipResponse {
+    pub membership: ShelterMembership,
+}
+
+impl Service {
+    async fn internal_get_shelter_membership(
+        &self,
+        shelter_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<ShelterMembership>> {
+        let pool = self.db_pool.clone();
+        let membership = spawn_blocking(
+            move || -> Result<Option<ShelterMembershipModel>> {
+                use schema::shelter_memberships as memberships;
+                let conn = pool.get().context("database connection failure")?;
+                memberships::table
+                    .filter(memberships::shelter_id.eq(shelter_id))
+                    .filter(memberships::user_id.eq(user_id))
+                    .first(&conn)
+                    .optional()
+                    .context("failed to load shelter membership model")
+            },
+        )
+        .await
+        .unwrap()?;
+        membership
+            .map(ShelterMembership::try_from)
+            .transpose()
+            .context("failed to decode shelter membership model")
+    }
+
+    /// Whether two users are members of a common shelter.
+    pub(super) async fn internal_share_shelter(
+        &self,
+        user_id: Uuid,
+        other_user_id: Uuid,
+    ) -> Result<bool> {
+        let pool = self.db_pool.clone();
+        let count = spawn_blocking(move || -> Result<i64> {
+            use schema::shelter_memberships as memberships;
+            let conn = pool.get().context("database connection failure")?;
+            let shelter_ids: Vec<Uuid> = memberships::table
+                .select(memberships::shelter_id)
+                .filter(memberships::user_id.eq(user_id))
+                .load(&conn)
+                .context("failed to load shelter IDs")?;
+            memberships::table
+                .filter(memberships::user_id.eq(other_user_id))
+                .filter(memberships::shelter_id.eq_any(shelter_ids))
+                .count()
+                .get_result(&conn)
+                .context("failed to count shared shelter memberships")
+        })
+        .await
+        .unwrap()?;
+        Ok(count > 0)
+    }
+
+    /// Get the viewer's role within a shelter, if any.
+    pub(super) async fn viewer_shelter_role(
+        &self,
+        context: &Context,
+        shelter_id: Uuid,
+    ) -> Result<Option<ShelterRole>> {
+        let viewer = match context.viewing_user() {
+            Some(user) => user,
+            None => return Ok(None),
+        };
+        let membership = self
+            .internal_get_shelter_membership(shelter_id, viewer.id)
+            .await?;
+        Ok(membership.map(|membership| membership.role))
+    }
+
+    /// Whether the viewer has at least the given role within a shelter.
+    pub(super) async fn has_shelter_role(
+        &self,
+        context: &Context,
+        shelter_id: Uuid,
+        role: ShelterRole,
+    ) -> Result<bool> {
+        let viewer_role = self.viewer_shelter_role(context, shelter_id).await?;
+        Ok(matches!(viewer_role, Some(viewer_role) if viewer_role >= role))
+    }
+
+    pub(super) async fn can_list_shelter_memberships(
+        &self,
+        context: &Context,
+        shelter_id: Uuid,
+    ) -> Result<bool> {
+        if context.is_privileged() {
+            return Ok(true);
+        }
+
+        // Shelter members can see each other.
+        self.has_shelter_role(context, shelter_id, ShelterRole::Viewer)
+            .await
+    }
+
+    /// Whether the viewer can grant or revoke the given role within a
+    /// shelter.
+    pub(super) async fn can_manage_shelter_role(
+        &self,
+        context: &Context,
+        shelter_id: Uuid,
+        role: ShelterRole,
+    ) -> Result<bool> {
+        if context.is_privileged() {
+            return Ok(true);
+        }
+
+        // Owners can manage all roles, and managers can manage the roles
+        // below their own.
+        let viewer_role = self.viewer_shelter_role(context, shelter_id).await?;
+        let can_manage = match viewer_role {
+            Some(ShelterRole::Owner) => true,
+            Some(ShelterRole::Manager) => role < ShelterRole::Manager,
+            _ => false,
+        };
+        Ok(can_manage)
+    }
+}
+
+impl Service {
+    pub async fn list_shelter_memberships(
+        &self,
+        context: &Context,
+        request: ListShelterMembershipsRequest,
+    ) -> Result<ListShelterMembershipsResponse> {
+        let ListShelterMembershipsRequest { shelter_id } = request;
+
+        // Assert shelter memberships are listable.
+        if !self
+            .can_list_shelter_memberships(context, shelter_id)
+            .await?
+        {
+            bail!("not authorized");
+        }
+
+        let memberships = {
+            let pool = self.db_pool.clone();
+            let memberships = spawn_blocking(
+                move || -> Result<Vec<ShelterMembershipModel>> {
+                    use schema::shelter_memberships as memberships;
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    memberships::table
+                        .filter(memberships::shelter_id.eq(shelter_id))
+                        .order(memberships::created_at.asc())
+                        .load(&conn)
+                        .context("failed to load shelter membership models")
+                },
+            )
+            .await
+            .unwrap()?;
+            memberships
+                .into_iter()
+                .map(ShelterMembership::try_from)
+                .collect::<Result<Vec<_>>>()
+                .context("failed to decode shelter membership models")?
+        };
+
+        let response = ListShelterMembershipsResponse { memberships };
+        Ok(response)
+    }
+
+    pub async fn grant_shelter_membership(
+        &self,
+        context: &Context,
+        request: GrantShelterMembershipRequest,
+    ) -> Result<GrantShelterMembershipResponse> {
+        let GrantShelterMembershipRequest {
+            shelter_id,
+            user_id,
+            role,
+        } = request;
+
+        // Assert role can be granted.
+        if !self
+            .can_manage_shelter_role(context, shelter_id, role)
+            .await?
+        {
+            bail!("not authorized");
+        }
+
+        // Fetch existing membership, whose role must also be manageable in
+        // order to be replaced.
+        let existing = self
+            .internal_get_shelter_membership(shelter_id, user_id)
+            .await?;
+        if let Some(existing) = &existing {
+            if !self
+                .can_manage_shelter_role(context, shelter_id, existing.role)
+                .await?
+            {
+                bail!("not authorized");
+            }
+        }
+
+        // Create or update membership.
+        let membership = match existing {
+            Some(mut membership) => {
+                membership.role = role;
+                membership.updated_at = Utc::now();
+                membership
+            }
+            None => {
+                let Meta {
+                    id,
+                    created_at,
+                    updated_at,
+                } = Meta::new();
+
+                ShelterMembership {
+                    id,
+                    created_at,
+                    updated_at,
+
+                    shelter_id,
+                    user_id,
+                    role,
+                }
+            }
+        };
+
+        // Upsert membership in database.
+        {
+            let pool = self.db_pool.clone();
+            let membership = ShelterMembershipModel::from(membership.clone());
+            spawn_blocking(move || -> Result<()> {
+                use schema::shelter_memberships as memberships;
+                let conn = pool.get().context("database connection failure")?;
+                conn.transaction(|| {
+                    if role != ShelterRole::Owner {
+                        assert_not_last_shelter_owner(
+                            &conn, shelter_id, user_id,
+                        )?;
+                    }
+                    insert_into(memberships::table)
+                        .values(&membership)
+                        .on_conflict((
+                            memberships::shelter_id,
+                            memberships::user_id,
+                        ))
+                        .do_update()
+                        .set((
+                            memberships::role.eq(&membership.role),
+                            memberships::updated_at.eq(membership.updated_at),
+                        ))
+                        .execute(&conn)
+                        .context("failed to upsert shelter membership model")?;
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap()?
+        };
+
+        let response = GrantShelterMembershipResponse { membership };
+        Ok(response)
+    }
+
+    pub async fn revoke_shelter_membership(
+        &self,
+        context: &Context,
+        request: RevokeShelterMembershipRequest,
+    ) -> Result<RevokeShelterMembershipResponse> {
+        let RevokeShelterMembershipRequest {
+            shelter_id,
+            user_id,
+        } = request;
+
+        // Assert shelter memberships are listable, so as not to reveal
+        // whether a membership exists.
+        if !self
+            .can_list_shelter_memberships(context, shelter_id)
+            .await?
+        {
+            bail!("not authorized");
+        }
+
+        // Fetch membership.
+        let membership = self
+            .internal_get_shelter_membership(shelter_id, user_id)
+            .await?
+            .context("membership not found")?;
+
+        // Assert role can be revoked.
+        if !self
+            .can_manage_shelter_role(context, shelter_id, membership.role)
+            .await?
+        {
+            bail!("not authorized");
+        }
+
+        // Delete membership.
+        {
+            let pool = self.db_pool.clone();
+            let membership_id = membership.id;
+            spawn_blocking(move || -> Result<()> {
+                use schema::shelter_memberships as memberships;
+                let conn = pool.get().context("database connection failure")?;
+                conn.transaction(|| {
+                    assert_not_last_shelter_owner(&conn, shelter_id, user_id)?;
+                    delete_from(memberships::table.find(membership_id))
+                        .execute(&conn)
+                        .context("failed to delete shelter membership model")?;
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap()?
+        };
+
+        let response = RevokeShelterMembershipResponse { membership };
+        Ok(response)
+    }
+}
+
+/// Assert that a user isn't the last owner of a shelter, locking the shelter's
+/// owner memberships until the end of the transaction.
+fn assert_not_last_shelter_owner(
+    conn: &PgConnection,
+    shelter_id: Uuid,
+    user_id: Uuid,
+) -> Result<()> {
+    use schema::shelter_memberships as memberships;
+    let owner_ids: Vec<Uuid> = memberships::table
+        .select(memberships::user_id)
+        .filter(memberships::shelter_id.eq(shelter_id))
+        .filter(memberships::role.eq(ShelterRole::Owner.to_string()))
+        .for_update()
+        .load(conn)
+        .context("failed to load shelter owner IDs")?;
+    if owner_ids == [user_id] {
+        bail!("cannot remove the last owner of a shelter");
+    }
+    Ok(())
+}
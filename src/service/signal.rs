@@ -6,6 +6,19 @@ use models::Signal as SignalModel;
 
 use models::SHELTER_COLUMNS;
 
+lazy_static! {
+    static ref MAX_SECRET_GRACE_PERIOD: ChronoDuration =
+        ChronoDuration::days(7);
+    static ref MAX_OBSERVATION_CLOCK_SKEW: ChronoDuration =
+        ChronoDuration::minutes(5);
+}
+
+/// The longest idempotency key that a signal can give with a measurement.
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// The largest number of observations that can be reported at once.
+pub const MAX_SIGNAL_OBSERVATIONS: usize = 1000;
+
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct Signal {
     pub id: Uuid,
@@ -18,7 +31,27 @@ pub struct Signal {
     pub shelter_id: Uuid,
     pub measure: ShelterMeasure,
 
-    pub secret: String,
+    pub secret_hash: SecretHash,
+    pub previous_secret: Option<PreviousSecret>,
+
+    /// When the signal was retired, after which it can no longer report
+    /// measurements.
+    pub archived_at: Option<DateTime>,
+
+    /// When the signal last reported a measurement or heartbeat.
+    pub last_seen_at: Option<DateTime>,
+
+    /// When the signal was disabled for repeatedly exceeding its rate limit,
+    /// after which it can't report measurements until its secret is rotated.
+    pub disabled_at: Option<DateTime>,
+}
+
+/// A `PreviousSecret` is a signal secret that has been rotated out, but
+/// remains valid until it expires.
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct PreviousSecret {
+    pub hash: SecretHash,
+    pub expires_at: DateTime,
 }
 
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
@@ -32,6 +65,10 @@ pub struct SignalProfile {
 
     pub shelter_id: Uuid,
     pub measure: ShelterMeasure,
+
+    pub archived_at: Option<DateTime>,
+    pub last_seen_at: Option<DateTime>,
+    pub disabled_at: Option<DateTime>,
 }
 
 impl From<Signal> for SignalProfile {
@@ -44,6 +81,9 @@ impl From<Signal> for SignalProfile {
             name,
             shelter_id,
             measure,
+            archived_at,
+            last_seen_at,
+            disabled_at,
             ..
         } = signal;
 
@@ -55,6 +95,9 @@ impl From<Signal> for SignalProfile {
             name,
             shelter_id,
             measure,
+            archived_at,
+            last_seen_at,
+            disabled_at,
         }
     }
 }
@@ -65,6 +108,30 @@ pub enum ShelterMeasure {
     Beds,
 }
 
+impl ShelterMeasure {
+    /// Get the part of a `ShelterSpace` that this measures.
+    pub fn get(&self, space: &ShelterSpace) -> u16 {
+        match self {
+            ShelterMeasure::Spots => space.spots,
+            ShelterMeasure::Beds => space.beds,
+        }
+    }
+
+    /// Replace the part of a `ShelterSpace` that this measures.
+    pub fn replace(&self, space: ShelterSpace, value: u16) -> ShelterSpace {
+        match self {
+            ShelterMeasure::Spots => ShelterSpace {
+                spots: value,
+                ..space
+            },
+            ShelterMeasure::Beds => ShelterSpace {
+                beds: value,
+                ..space
+            },
+        }
+    }
+}
+
 impl Display for ShelterMeasure {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         let s = to_plain_string(self).map_err(|_| FmtError)?;
@@ -121,38 +188,77 @@ pub struct GetSignalShelterResponse {
 }
 
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
-pub struct GetSignalSecretRequest {
-    pub signal_id: Uuid,
+pub struct ListSignalProfilesRequest {
+    pub limit: u32,
+    pub offset: u32,
+    pub include_archived: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GetSignalSecretResponse {
-    pub secret: String,
+pub struct ListSignalProfilesResponse {
+    pub profiles: Vec<SignalProfile>,
 }
 
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
-pub struct ListSignalProfilesRequest {
-    pub limit: u32,
-    pub offset: u32,
+pub struct CountSignalProfilesRequest {
+    pub include_archived: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ListSignalProfilesResponse {
-    pub profiles: Vec<SignalProfile>,
+pub struct CountSignalProfilesResponse {
+    pub count: u32,
 }
 
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct ListSignalMeasurementsRequest {
     pub signal_id: Uuid,
+    pub filter: ShelterMeasurementFilter,
+
+    /// Only include measurements older than this key.
+    pub after: Option<ShelterMeasurementKey>,
+
+    /// Only include measurements newer than this key.
+    pub before: Option<ShelterMeasurementKey>,
+
     pub limit: u32,
-    pub offset: u32,
+
+    /// If set, select the oldest matching measurements instead of the
+    /// newest.
+    pub from_end: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListSignalMeasurementsResponse {
+    /// Measurements, sorted from newest to oldest.
+    pub measurements: Vec<ShelterMeasurement>,
+
+    /// Whether more measurements match beyond the selected ones.
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct GetLatestSignalMeasurementsRequest {
+    pub signal_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetLatestSignalMeasurementsResponse {
+    /// The newest measurement of each signal that has any, in no particular
+    /// order.
     pub measurements: Vec<ShelterMeasurement>,
 }
 
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct CountSignalMeasurementsRequest {
+    pub signal_id: Uuid,
+    pub filter: ShelterMeasurementFilter,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountSignalMeasurementsResponse {
+    pub count: u32,
+}
+
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct CreateSignalRequest {
     pub name: InputString,
@@ -163,13 +269,52 @@ pub struct CreateSignalRequest {
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct CreateSignalResponse {
     pub signal: Signal,
+
+    /// The signal's secret, which is only available upon creation.
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct VerifySignalSecretRequest {
+    pub signal_id: Uuid,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct VerifySignalSecretResponse {
+    /// Whether the signal exists and has the given secret.
+    pub is_valid: bool,
+
+    /// Whether the signal has been archived, in which case it can no longer
+    /// report measurements.
+    pub is_archived: bool,
+
+    /// Whether the signal has been disabled, in which case it can't report
+    /// measurements until its secret is rotated.
+    pub is_disabled: bool,
 }
 
+/// A `SignalMeasurement` is a value reported by a `Signal`.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignalMeasurement {
+    /// A count that replaces the shelter's current occupancy.
+    Absolute(u16),
+
+    /// A change to the shelter's current occupancy, such as `1` when someone
+    /// enters and `-1` when they leave.
+    Delta(i32),
+}
+
+/// A request to record a measurement reported by a signal, which must be
+/// made on behalf of that signal (see `Principal::Signal`).
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct CreateSignalMeasurementRequest {
     pub signal_id: Uuid,
-    pub signal_secret: String,
-    pub measurement: u16,
+    pub measurement: SignalMeasurement,
+
+    /// A key that identifies this report, so that retrying it returns the
+    /// original response rather than creating another measurement.
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -178,6 +323,95 @@ pub struct CreateSignalMeasurementResponse {
     pub measurement: ShelterMeasurement,
 }
 
+/// A `SignalObservation` is an absolute measurement that a `Signal` made at
+/// a particular time, which may have been reported later.
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct SignalObservation {
+    pub observed_at: DateTime,
+    pub measurement: u16,
+}
+
+/// A request to record a batch of observations reported by a signal, such
+/// as those it made while offline.
+///
+/// Observations are stored as measurements created at the time they were
+/// observed, and only update the shelter's occupancy if they are newer than
+/// the measurement it currently reflects.
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct CreateSignalMeasurementsRequest {
+    pub signal_id: Uuid,
+    pub observations: Vec<SignalObservation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSignalMeasurementsResponse {
+    pub shelter: Shelter,
+
+    /// The created measurements, from oldest to newest.
+    pub measurements: Vec<ShelterMeasurement>,
+
+    /// The observations that weren't recorded, because they exceeded the
+    /// shelter's capacity under `ShelterCapacityPolicy::Reject`.
+    pub rejected: Vec<RejectedSignalObservation>,
+}
+
+/// A `RejectedSignalObservation` is an observation that wasn't recorded, and
+/// why.
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct RejectedSignalObservation {
+    pub observation: SignalObservation,
+    pub reason: String,
+}
+
+/// The error returned when a signal's measurement report is malformed.
+#[derive(Debug, Clone)]
+pub struct InvalidMeasurementError {
+    pub reason: &'static str,
+}
+
+impl InvalidMeasurementError {
+    pub fn new(reason: &'static str) -> Self {
+        Self { reason }
+    }
+}
+
+impl Display for InvalidMeasurementError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.reason.fmt(f)
+    }
+}
+
+impl std::error::Error for InvalidMeasurementError {}
+
+/// A request to record a heartbeat from a signal, which reports that it is
+/// still online without measuring anything. It must be made on behalf of
+/// that signal (see `Principal::Signal`).
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct PingSignalRequest {
+    pub signal_id: Uuid,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct PingSignalResponse {
+    pub signal: Signal,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct RotateSignalSecretRequest {
+    pub signal_id: Uuid,
+
+    /// How long the current secret remains valid after rotation, if at all.
+    pub grace_period: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct RotateSignalSecretResponse {
+    pub signal: Signal,
+
+    /// The signal's new secret, which is only available upon rotation.
+    pub secret: String,
+}
+
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct DeleteSignalRequest {
     pub signal_id: Uuid,
@@ -189,16 +423,53 @@ pub struct DeleteSignalResponse {
 }
 
 impl Service {
+    pub(super) async fn internal_get_signal_shelter_id(
+        &self,
+        signal_id: Uuid,
+    ) -> Result<Uuid> {
+        let pool = self.db_pool.clone();
+        spawn_blocking(move || -> Result<Uuid> {
+            use schema::signals;
+            let conn = pool.get().context("database connection failure")?;
+            signals::table
+                .find(signal_id)
+                .select(signals::shelter_id)
+                .first(&conn)
+                .context("failed to load signal shelter ID")
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Whether the viewer has at least the given role within the shelter
+    /// that a signal belongs to.
+    async fn has_signal_shelter_role(
+        &self,
+        context: &Context,
+        signal_id: Uuid,
+        role: ShelterRole,
+    ) -> Result<bool> {
+        // Only users can be shelter members.
+        if context.viewing_user().is_none() {
+            return Ok(false);
+        }
+
+        let shelter_id = self.internal_get_signal_shelter_id(signal_id).await?;
+        self.has_shelter_role(context, shelter_id, role).await
+    }
+
     pub(super) async fn _can_list_signals(
         &self,
         context: &Context,
+        shelter_id: Uuid,
     ) -> Result<bool> {
-        if context.is_internal() {
+        if context.is_privileged() {
             return Ok(true);
         }
 
-        // Listing signals is restricted.
-        Ok(false)
+        // Shelter members can list the shelter's signals.
+        self.has_shelter_role(context, shelter_id, ShelterRole::Viewer)
+            .await
     }
 
     pub(super) async fn can_list_signal_profiles(
@@ -212,14 +483,73 @@ impl Service {
     pub(super) async fn can_view_signal(
         &self,
         context: &Context,
-        _signal_id: Uuid,
+        signal_id: Uuid,
+    ) -> Result<bool> {
+        if context.is_privileged() {
+            return Ok(true);
+        }
+
+        // Signals can view themselves.
+        if context.viewing_signal_id() == Some(signal_id) {
+            return Ok(true);
+        }
+
+        // Integrations can view all signals.
+        if context.viewing_api_key_id().is_some() {
+            return Ok(true);
+        }
+
+        // Shelter members can view the shelter's signals.
+        self.has_signal_shelter_role(context, signal_id, ShelterRole::Viewer)
+            .await
+    }
+
+    /// Whether all of the given signals are viewable, which is checked for
+    /// members with a single query.
+    pub(super) async fn can_view_signals(
+        &self,
+        context: &Context,
+        signal_ids: &[Uuid],
     ) -> Result<bool> {
-        if context.is_internal() {
+        if context.is_privileged() {
             return Ok(true);
         }
 
-        // Viewing signals is restricted.
-        Ok(false)
+        // Signals can view themselves.
+        if let Some(viewing_signal_id) = context.viewing_signal_id() {
+            let can_view = signal_ids.iter().all(|id| *id == viewing_signal_id);
+            return Ok(can_view);
+        }
+
+        // Integrations can view all signals.
+        if context.viewing_api_key_id().is_some() {
+            return Ok(true);
+        }
+
+        // Shelter members can view the shelter's signals.
+        let user_id = match context.viewing_user() {
+            Some(user) => user.id,
+            None => return Ok(signal_ids.is_empty()),
+        };
+        let signal_ids: Set<Uuid> = signal_ids.iter().copied().collect();
+        let expected = signal_ids.len();
+        let pool = self.db_pool.clone();
+        let count = spawn_blocking(move || -> Result<i64> {
+            use schema::{shelter_memberships, signals};
+            let conn = pool.get().context("database connection failure")?;
+            let shelter_ids = shelter_memberships::table
+                .filter(shelter_memberships::user_id.eq(user_id))
+                .select(shelter_memberships::shelter_id);
+            signals::table
+                .filter(signals::id.eq_any(signal_ids))
+                .filter(signals::shelter_id.eq_any(shelter_ids))
+                .count()
+                .get_result(&conn)
+                .context("failed to count signals")
+        })
+        .await
+        .unwrap()?;
+        Ok(count == expected as i64)
     }
 
     pub(super) async fn can_view_signal_profile(
@@ -231,17 +561,307 @@ impl Service {
         Ok(true)
     }
 
+    /// Whether a signal last seen at the given time is stale, i.e. it hasn't
+    /// reported a measurement or heartbeat recently enough to be trusted.
+    pub fn is_signal_stale(&self, last_seen_at: Option<&DateTime>) -> bool {
+        match last_seen_at {
+            Some(last_seen_at) => {
+                Utc::now() - *last_seen_at > self.signal_stale_after
+            }
+            None => true,
+        }
+    }
+
+    pub(super) async fn can_report_signal_measurement(
+        &self,
+        context: &Context,
+        signal_id: Uuid,
+    ) -> Result<bool> {
+        // Only signals can report their own measurements.
+        Ok(context.viewing_signal_id() == Some(signal_id))
+    }
+
     pub(super) async fn can_edit_signal(
         &self,
         context: &Context,
-        _signal_id: Uuid,
+        signal_id: Uuid,
     ) -> Result<bool> {
-        if context.is_internal() {
+        if context.is_privileged() {
             return Ok(true);
         }
 
-        // Editing signals is restricted.
-        Ok(false)
+        // Shelter managers can edit the shelter's signals.
+        self.has_signal_shelter_role(context, signal_id, ShelterRole::Manager)
+            .await
+    }
+
+    /// Take a token from the rate limit of the address a request is made
+    /// from, failing with a `RateLimitedError` if it is exhausted.
+    fn internal_throttle_address(&self, context: &Context) -> Result<()> {
+        if let Some(remote_addr) = context.remote_addr {
+            let addr = rate_limited_address(remote_addr);
+            if !self.address_rate_limiter.check(&addr) {
+                let limit = self.address_rate_limiter.limit();
+                return Err(RateLimitedError { limit }.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Take a token from a signal's rate limit, failing with a
+    /// `RateLimitedError` if it is exhausted.
+    ///
+    /// Signals that exceed their rate limit too often are disabled.
+    async fn internal_throttle_signal(&self, signal_id: Uuid) -> Result<()> {
+        if self.signal_rate_limiter.check(&signal_id) {
+            return Ok(());
+        }
+
+        // Disable signal if it has been throttled too often.
+        if !self.signal_violation_limiter.check(&signal_id) {
+            let pool = self.db_pool.clone();
+            let disabled = spawn_blocking(move || -> Result<bool> {
+                use schema::signals;
+                let conn = pool.get().context("database connection failure")?;
+                let now = Utc::now();
+                let updated = update(
+                    signals::table
+                        .find(signal_id)
+                        .filter(signals::disabled_at.is_null()),
+                )
+                .set((
+                    signals::disabled_at.eq(now),
+                    signals::updated_at.eq(now),
+                ))
+                .execute(&conn)
+                .context("failed to update signal model")?;
+                Ok(updated > 0)
+            })
+            .await
+            .unwrap()?;
+            if disabled {
+                warn!(
+                    "disabled signal {} for repeatedly exceeding its rate limit",
+                    signal_id
+                );
+            }
+        }
+
+        let limit = self.signal_rate_limiter.limit();
+        Err(RateLimitedError { limit }.into())
+    }
+
+    /// Get the response to a measurement that a signal already reported
+    /// with the given idempotency key, if any.
+    async fn internal_replay_signal_measurement(
+        &self,
+        context: &Context,
+        signal_id: Uuid,
+        idempotency_key: String,
+    ) -> Result<Option<CreateSignalMeasurementResponse>> {
+        // Fetch measurement.
+        let measurement = {
+            let pool = self.db_pool.clone();
+            let measurement = spawn_blocking(
+                move || -> Result<Option<ShelterMeasurementModel>> {
+                    use schema::shelter_measurements as measurements;
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    measurements::table
+                        .filter(measurements::signal_id.eq(signal_id))
+                        .filter(
+                            measurements::idempotency_key.eq(idempotency_key),
+                        )
+                        .first(&conn)
+                        .optional()
+                        .context("failed to load measurement model")
+                },
+            )
+            .await
+            .unwrap()?;
+            let measurement = measurement
+                .map(ShelterMeasurement::try_from)
+                .transpose()
+                .context("failed to decode measurement model")?;
+            match measurement {
+                Some(measurement) => measurement,
+                None => return Ok(None),
+            }
+        };
+
+        // Fetch shelter.
+        let shelter = {
+            let context = context.internal();
+            let request = GetShelterRequest {
+                shelter_id: measurement.shelter_id,
+            };
+            let response = self
+                .get_shelter(&context, request)
+                .await
+                .context("failed to get shelter")?;
+            response.shelter.context("shelter not found")?
+        };
+
+        let response = CreateSignalMeasurementResponse {
+            shelter,
+            measurement,
+        };
+        Ok(Some(response))
+    }
+
+    /// Update a shelter's occupancy with a measurement from one of its
+    /// signals, and record the resulting snapshot.
+    ///
+    /// The shelter is locked while its occupancy is read, updated, and
+    /// written back, so that concurrent measurements don't overwrite one
+    /// another.
+    async fn internal_update_shelter_occupancy(
+        &self,
+        signal: &Signal,
+        measurement: SignalMeasurement,
+        idempotency_key: Option<String>,
+    ) -> Result<(Shelter, ShelterMeasurement)> {
+        let Meta {
+            id,
+            created_at,
+            updated_at,
+        } = Meta::new();
+
+        let pool = self.db_pool.clone();
+        let shelter_id = signal.shelter_id;
+        let signal_id = signal.id;
+        let measure = signal.measure.clone();
+        spawn_blocking(move || -> Result<(Shelter, ShelterMeasurement)> {
+            use schema::shelter_measurements as measurements;
+            use schema::{shelters, signals};
+            let conn = pool.get().context("database connection failure")?;
+            conn.transaction(|| {
+                let shelter: ShelterModel = shelters::table
+                    .find(shelter_id)
+                    .select(SHELTER_COLUMNS)
+                    .for_update()
+                    .first(&conn)
+                    .context("failed to load shelter model")?;
+                let mut shelter = Shelter::try_from(shelter)
+                    .context("failed to decode shelter model")?;
+
+                // Determine the signal's new reading, and validate it
+                // against the shelter's capacity.
+                let occupancy = shelter.occupancy.clone().unwrap_or_default();
+                let mut readings =
+                    load_signal_readings(&conn, shelter_id, &measure)?;
+                let delta = match measurement {
+                    SignalMeasurement::Delta(delta) => Some(delta),
+                    SignalMeasurement::Absolute(_) => None,
+                };
+                let reading = match measurement {
+                    SignalMeasurement::Absolute(value) => i64::from(value),
+                    SignalMeasurement::Delta(delta) => {
+                        // Under the latest aggregation, deltas adjust the
+                        // shelter's occupancy, rather than the signal's own
+                        // previous reading.
+                        let previous = match shelter.aggregation {
+                            ShelterAggregation::Latest => {
+                                measure.get(&occupancy)
+                            }
+                            _ => readings
+                                .iter()
+                                .find(|reading| reading.signal_id == signal_id)
+                                .map(|reading| reading.value)
+                                .unwrap_or_default(),
+                        };
+                        i64::from(previous) + i64::from(delta)
+                    }
+                };
+                let capacity = measure.get(&shelter.capacity);
+                let policy = shelter.capacity_policy;
+                let (value, mut status) = policy.apply(reading, capacity)?;
+
+                // Aggregate shelter occupancy with the new reading, unless it
+                // was flagged, and validate it against the shelter's
+                // capacity.
+                let occupancy = if status == ShelterMeasurementStatus::Flagged {
+                    occupancy
+                } else {
+                    readings.retain(|reading| reading.signal_id != signal_id);
+                    readings.push(SignalReading {
+                        signal_id,
+                        created_at,
+                        value,
+                    });
+                    let aggregate =
+                        aggregate_signal_readings(&shelter, &readings);
+                    let occupancy = match aggregate {
+                        Some(aggregate) => {
+                            let (aggregate, is_clamped) =
+                                policy.apply_aggregate(aggregate, capacity)?;
+                            if is_clamped {
+                                status = ShelterMeasurementStatus::Clamped;
+                            }
+                            measure.replace(occupancy, aggregate)
+                        }
+                        None => occupancy,
+                    };
+
+                    // Update shelter occupancy.
+                    shelter.occupancy = Some(occupancy.clone());
+                    shelter.occupancy_updated_at = Some(created_at);
+                    shelter.updated_at = updated_at;
+                    let ShelterSpace { spots, beds } = &occupancy;
+                    update(shelters::table.find(shelter_id))
+                        .set((
+                            shelters::occupied_spots.eq(i32::from(*spots)),
+                            shelters::occupied_beds.eq(i32::from(*beds)),
+                            shelters::occupancy_updated_at.eq(created_at),
+                            shelters::updated_at.eq(updated_at),
+                        ))
+                        .execute(&conn)
+                        .context("failed to update shelter model")?;
+                    occupancy
+                };
+
+                // Mark signal as seen.
+                update(signals::table.find(signal_id))
+                    .set(signals::last_seen_at.eq(created_at))
+                    .execute(&conn)
+                    .context("failed to update signal model")?;
+
+                // Create measurement.
+                let measurement = ShelterMeasurement {
+                    id,
+                    created_at,
+                    updated_at,
+
+                    shelter_id,
+                    signal_id: Some(signal_id),
+                    user_id: None,
+
+                    capacity: shelter.capacity.clone(),
+                    occupancy,
+                    value: Some(value),
+                    delta,
+                    status,
+
+                    retracted_at: None,
+                    retracted_by_id: None,
+                    retraction_reason: None,
+                    correction_of_id: None,
+
+                    idempotency_key,
+                };
+                insert_into(measurements::table)
+                    .values(
+                        ShelterMeasurementModel::try_from(measurement.clone())
+                            .context("failed to encode measurement")?,
+                    )
+                    .execute(&conn)
+                    .context("failed to insert measurement model")?;
+
+                Ok((shelter, measurement))
+            })
+        })
+        .await
+        .unwrap()
     }
 }
 
@@ -313,7 +933,9 @@ impl Service {
         };
 
         // Assert profile is viewable.
-        if profile.is_some() && !self.can_view_signal_profile(context, signal_id).await? {
+        if profile.is_some()
+            && !self.can_view_signal_profile(context, signal_id).await?
+        {
             bail!("not authorized");
         }
 
@@ -361,37 +983,6 @@ impl Service {
         Ok(response)
     }
 
-    pub async fn get_signal_secret(
-        &self,
-        context: &Context,
-        request: GetSignalSecretRequest,
-    ) -> Result<GetSignalSecretResponse> {
-        let GetSignalSecretRequest { signal_id } = request;
-
-        // Onluy permit internal access.
-        if !context.is_internal() {
-            bail!("not authorized");
-        }
-
-        let secret = {
-            let pool = self.db_pool.clone();
-            spawn_blocking(move || -> Result<String> {
-                use schema::signals;
-                let conn = pool.get().context("database connection failure")?;
-                signals::table
-                    .find(signal_id)
-                    .select(signals::secret)
-                    .first(&conn)
-                    .context("failed to load signal secret")
-            })
-            .await
-            .unwrap()?
-        };
-
-        let response = GetSignalSecretResponse { secret };
-        Ok(response)
-    }
-
     pub async fn get_signal_shelter(
         &self,
         context: &Context,
@@ -430,7 +1021,11 @@ impl Service {
         context: &Context,
         request: ListSignalProfilesRequest,
     ) -> Result<ListSignalProfilesResponse> {
-        let ListSignalProfilesRequest { limit, offset } = request;
+        let ListSignalProfilesRequest {
+            limit,
+            offset,
+            include_archived,
+        } = request;
 
         if !self.can_list_signal_profiles(context).await? {
             bail!("not authorized")
@@ -443,7 +1038,11 @@ impl Service {
                     use schema::signals;
                     let conn =
                         pool.get().context("database connection failure")?;
-                    signals::table
+                    let mut query = signals::table.into_boxed();
+                    if !include_archived {
+                        query = query.filter(signals::archived_at.is_null());
+                    }
+                    query
                         .limit(limit.into())
                         .offset(offset.into())
                         .load(&conn)
@@ -462,6 +1061,40 @@ impl Service {
         Ok(response)
     }
 
+    pub async fn count_signal_profiles(
+        &self,
+        context: &Context,
+        request: CountSignalProfilesRequest,
+    ) -> Result<CountSignalProfilesResponse> {
+        let CountSignalProfilesRequest { include_archived } = request;
+
+        if !self.can_list_signal_profiles(context).await? {
+            bail!("not authorized")
+        }
+
+        let count = {
+            let pool = self.db_pool.clone();
+            let count = spawn_blocking(move || -> Result<i64> {
+                use schema::signals;
+                let conn = pool.get().context("database connection failure")?;
+                let mut query = signals::table.into_boxed();
+                if !include_archived {
+                    query = query.filter(signals::archived_at.is_null());
+                }
+                query
+                    .count()
+                    .get_result(&conn)
+                    .context("failed to count signals")
+            })
+            .await
+            .unwrap()?;
+            count.try_into().context("invalid signal count")?
+        };
+
+        let response = CountSignalProfilesResponse { count };
+        Ok(response)
+    }
+
     pub async fn list_signal_measurements(
         &self,
         context: &Context,
@@ -470,8 +1103,11 @@ impl Service {
         dbg!(&request);
         let ListSignalMeasurementsRequest {
             signal_id,
+            filter,
+            after,
+            before,
             limit,
-            offset,
+            from_end,
         } = request;
 
         // Assert signal is viewable.
@@ -480,6 +1116,63 @@ impl Service {
         }
 
         // List measurements.
+        let (measurements, has_more) = {
+            let pool = self.db_pool.clone();
+            let mut models = spawn_blocking(
+                move || -> Result<Vec<ShelterMeasurementModel>> {
+                    use schema::shelter_measurements as measurements;
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    let query = measurements::table
+                        .filter(measurements::signal_id.eq(signal_id))
+                        .into_boxed();
+                    let query = filter_shelter_measurements(query, filter);
+                    let query = seek_shelter_measurements(
+                        query, after, before, limit, from_end,
+                    );
+                    query
+                        .load(&conn)
+                        .context("failed to load shelter measurement models")
+                },
+            )
+            .await
+            .unwrap()?;
+
+            // Trim the lookahead measurement, and restore newest-first order.
+            let has_more = models.len() > limit as usize;
+            models.truncate(limit as usize);
+            if from_end {
+                models.reverse();
+            }
+
+            let measurements = models
+                .into_iter()
+                .map(ShelterMeasurement::try_from)
+                .collect::<Result<Vec<_>>>()
+                .context("failed to decode shelter measurement models")?;
+            (measurements, has_more)
+        };
+
+        let response = ListSignalMeasurementsResponse {
+            measurements,
+            has_more,
+        };
+        Ok(response)
+    }
+
+    pub async fn get_latest_signal_measurements(
+        &self,
+        context: &Context,
+        request: GetLatestSignalMeasurementsRequest,
+    ) -> Result<GetLatestSignalMeasurementsResponse> {
+        let GetLatestSignalMeasurementsRequest { signal_ids } = request;
+
+        // Assert signals are viewable.
+        if !self.can_view_signals(context, &signal_ids).await? {
+            bail!("not authorized");
+        }
+
+        // Load the newest measurement of each signal.
         let measurements = {
             let pool = self.db_pool.clone();
             let models = spawn_blocking(
@@ -488,10 +1181,14 @@ impl Service {
                     let conn =
                         pool.get().context("database connection failure")?;
                     measurements::table
-                        .filter(measurements::signal_id.eq(signal_id))
-                        .order(measurements::created_at.desc())
-                        .limit(limit.into())
-                        .offset(offset.into())
+                        .filter(measurements::signal_id.eq_any(signal_ids))
+                        .filter(measurements::retracted_at.is_null())
+                        .distinct_on(measurements::signal_id)
+                        .order((
+                            measurements::signal_id,
+                            measurements::created_at.desc(),
+                            measurements::id.desc(),
+                        ))
                         .load(&conn)
                         .context("failed to load shelter measurement models")
                 },
@@ -505,7 +1202,45 @@ impl Service {
                 .context("failed to decode shelter measurement models")?
         };
 
-        let response = ListSignalMeasurementsResponse { measurements };
+        let response = GetLatestSignalMeasurementsResponse { measurements };
+        Ok(response)
+    }
+
+    pub async fn count_signal_measurements(
+        &self,
+        context: &Context,
+        request: CountSignalMeasurementsRequest,
+    ) -> Result<CountSignalMeasurementsResponse> {
+        let CountSignalMeasurementsRequest { signal_id, filter } = request;
+
+        // Assert signal is viewable.
+        if !self.can_view_signal(context, signal_id).await? {
+            bail!("not authorized");
+        }
+
+        // Count measurements.
+        let count = {
+            let pool = self.db_pool.clone();
+            let count = spawn_blocking(move || -> Result<i64> {
+                use schema::shelter_measurements as measurements;
+                let conn = pool.get().context("database connection failure")?;
+                let query = measurements::table
+                    .filter(measurements::signal_id.eq(signal_id))
+                    .into_boxed();
+                let query = filter_shelter_measurements(query, filter);
+                query
+                    .count()
+                    .get_result(&conn)
+                    .context("failed to count shelter measurements")
+            })
+            .await
+            .unwrap()?;
+            count
+                .try_into()
+                .context("invalid shelter measurement count")?
+        };
+
+        let response = CountSignalMeasurementsResponse { count };
         Ok(response)
     }
 
@@ -520,12 +1255,13 @@ impl Service {
             measure,
         } = request;
 
-        // Restrict shelter creation.
-        if !context.is_internal() {
+        // Signals can be added by those who can edit the shelter.
+        if !self.can_edit_shelter(context, shelter_id).await? {
             bail!("not authorized");
         }
 
         // Create signal.
+        let secret = generate_secret();
         let signal = {
             let Meta {
                 id,
@@ -544,7 +1280,12 @@ impl Service {
                 shelter_id,
                 measure,
 
-                secret: Uuid::new_v4().to_string(),
+                secret_hash: SecretHash::new(&secret),
+                previous_secret: None,
+
+                archived_at: None,
+                last_seen_at: None,
+                disabled_at: None,
             }
         };
 
@@ -566,7 +1307,71 @@ impl Service {
             .unwrap()?
         };
 
-        let response = CreateSignalResponse { signal };
+        let response = CreateSignalResponse { signal, secret };
+        Ok(response)
+    }
+
+    pub async fn verify_signal_secret(
+        &self,
+        context: &Context,
+        request: VerifySignalSecretRequest,
+    ) -> Result<VerifySignalSecretResponse> {
+        let VerifySignalSecretRequest { signal_id, secret } = request;
+
+        // Assert address is within its rate limit, so that it can't guess
+        // secrets freely.
+        self.internal_throttle_address(context)?;
+
+        // Fetch signal.
+        let signal = {
+            let pool = self.db_pool.clone();
+            let signal =
+                spawn_blocking(move || -> Result<Option<SignalModel>> {
+                    use schema::signals;
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    signals::table
+                        .find(signal_id)
+                        .first(&conn)
+                        .optional()
+                        .context("failed to load signal model")
+                })
+                .await
+                .unwrap()?;
+            signal
+                .map(Signal::try_from)
+                .transpose()
+                .context("failed to decode signal model")?
+        };
+
+        // Check the secret against both the current and previous secrets,
+        // without short-circuiting.
+        let is_valid = match &signal {
+            Some(signal) => {
+                let is_current = signal.secret_hash.verify(&secret);
+                let is_previous = match &signal.previous_secret {
+                    Some(PreviousSecret { hash, expires_at }) => {
+                        hash.verify(&secret) && *expires_at > Utc::now()
+                    }
+                    None => false,
+                };
+                is_current | is_previous
+            }
+            None => false,
+        };
+        let is_archived = matches!(
+            &signal,
+            Some(signal) if signal.archived_at.is_some()
+        );
+        let is_disabled = matches!(
+            signal,
+            Some(signal) if signal.disabled_at.is_some()
+        );
+        let response = VerifySignalSecretResponse {
+            is_valid,
+            is_archived,
+            is_disabled,
+        };
         Ok(response)
     }
 
@@ -578,10 +1383,41 @@ impl Service {
         dbg!(&request);
         let CreateSignalMeasurementRequest {
             signal_id,
-            signal_secret,
             measurement,
+            idempotency_key,
         } = request;
 
+        // Assert signal can report measurements.
+        if !self
+            .can_report_signal_measurement(context, signal_id)
+            .await?
+        {
+            bail!("not authorized")
+        }
+
+        // Assert signal is within its rate limit.
+        self.internal_throttle_signal(signal_id).await?;
+
+        // Replay the original response if this report is a retry.
+        if let Some(idempotency_key) = &idempotency_key {
+            if idempotency_key.is_empty() {
+                bail!(InvalidMeasurementError::new("empty idempotency key"))
+            }
+            if idempotency_key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+                bail!(InvalidMeasurementError::new("idempotency key too long"))
+            }
+            let response = self
+                .internal_replay_signal_measurement(
+                    context,
+                    signal_id,
+                    idempotency_key.to_owned(),
+                )
+                .await?;
+            if let Some(response) = response {
+                return Ok(response);
+            }
+        }
+
         // Fetch signal.
         let signal = {
             let context = context.internal();
@@ -592,100 +1428,418 @@ impl Service {
                 .context("failed to get signal")?;
             response.signal.context("signal not found")?
         };
+        if signal.archived_at.is_some() {
+            bail!("signal is archived")
+        }
+        if signal.disabled_at.is_some() {
+            bail!("signal is disabled")
+        }
+
+        // Update shelter occupancy and create measurement.
+        let result = self
+            .internal_update_shelter_occupancy(
+                &signal,
+                measurement,
+                idempotency_key.clone(),
+            )
+            .await;
+        let (shelter, measurement) = match (result, idempotency_key) {
+            (Ok(created), _) => created,
+
+            // A concurrent retry may have created the measurement first, in
+            // which case the idempotency key is already taken.
+            (Err(error), Some(idempotency_key)) => {
+                let response = self
+                    .internal_replay_signal_measurement(
+                        context,
+                        signal_id,
+                        idempotency_key,
+                    )
+                    .await?;
+                return response.ok_or(error);
+            }
+            (Err(error), None) => return Err(error),
+        };
 
-        // Ensure signal secret matches.
-        if signal.secret != signal_secret {
+        // Score measurement for anomalies.
+        self.internal_detect_measurement_anomalies(
+            &signal,
+            vec![measurement.clone()],
+        );
+
+        // Notify occupancy watchers, unless the measurement was flagged.
+        if measurement.status != ShelterMeasurementStatus::Flagged {
+            self.occupancy_bus.publish(ShelterOccupancyEvent {
+                shelter: shelter.clone(),
+                measurement: measurement.clone(),
+            });
+        }
+
+        let response = CreateSignalMeasurementResponse {
+            shelter,
+            measurement,
+        };
+        Ok(response)
+    }
+
+    pub async fn create_signal_measurements(
+        &self,
+        context: &Context,
+        request: CreateSignalMeasurementsRequest,
+    ) -> Result<CreateSignalMeasurementsResponse> {
+        let CreateSignalMeasurementsRequest {
+            signal_id,
+            mut observations,
+        } = request;
+
+        // Assert signal can report measurements.
+        if !self
+            .can_report_signal_measurement(context, signal_id)
+            .await?
+        {
             bail!("not authorized")
         }
 
-        // Fetch shelter.
-        let mut shelter = {
+        // Assert signal is within its rate limit.
+        self.internal_throttle_signal(signal_id).await?;
+
+        // Validate observations.
+        if observations.is_empty() {
+            bail!(InvalidMeasurementError::new("no observations"))
+        }
+        if observations.len() > MAX_SIGNAL_OBSERVATIONS {
+            bail!(InvalidMeasurementError::new("too many observations"))
+        }
+        let max_observed_at = Utc::now() + *MAX_OBSERVATION_CLOCK_SKEW;
+        if observations
+            .iter()
+            .any(|observation| observation.observed_at > max_observed_at)
+        {
+            bail!(InvalidMeasurementError::new(
+                "observations cannot be in the future"
+            ))
+        }
+
+        // Fetch signal.
+        let signal = {
             let context = context.internal();
-            let request = GetShelterRequest {
-                shelter_id: signal.shelter_id,
-            };
+            let request = GetSignalRequest { signal_id };
             let response = self
-                .get_shelter(&context, request)
+                .get_signal(&context, request)
                 .await
-                .context("failed to get shelter")?;
-            response.shelter.context("shelter not found")?
+                .context("failed to get signal")?;
+            response.signal.context("signal not found")?
         };
+        if signal.archived_at.is_some() {
+            bail!("signal is archived")
+        }
+        if signal.disabled_at.is_some() {
+            bail!("signal is disabled")
+        }
 
-        // Create capacity and occupancy snapshots.
-        let capacity = shelter.capacity.to_owned();
-        let occupancy = {
-            let occupancy = shelter.occupancy.to_owned().unwrap_or_default();
-            dbg!(&occupancy);
-            match signal.measure {
-                ShelterMeasure::Spots => ShelterSpace {
-                    spots: measurement,
-                    ..occupancy
-                },
-                ShelterMeasure::Beds => ShelterSpace {
-                    beds: measurement,
-                    ..occupancy
+        // Store observations in the order they were made.
+        observations.sort_by_key(|observation| observation.observed_at);
+
+        // Create measurements, and update shelter occupancy if they change
+        // it.
+        let (shelter, measurements, rejected, is_changed) = {
+            let pool = self.db_pool.clone();
+            let shelter_id = signal.shelter_id;
+            let measure = signal.measure.clone();
+            spawn_blocking(
+                move || -> Result<(
+                    Shelter,
+                    Vec<ShelterMeasurement>,
+                    Vec<RejectedSignalObservation>,
+                    bool,
+                )> {
+                    use schema::shelter_measurements as measurements;
+                    use schema::{shelters, signals};
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    conn.transaction(|| {
+                        let shelter: ShelterModel = shelters::table
+                            .find(shelter_id)
+                            .select(SHELTER_COLUMNS)
+                            .for_update()
+                            .first(&conn)
+                            .context("failed to load shelter model")?;
+                        let mut shelter = Shelter::try_from(shelter)
+                            .context("failed to decode shelter model")?;
+
+                        // Validate each observation against the shelter's
+                        // capacity, and snapshot it against the current
+                        // capacity and occupancy.
+                        //
+                        // Observations that the shelter rejects are skipped,
+                        // rather than failing the whole batch.
+                        let occupancy =
+                            shelter.occupancy.clone().unwrap_or_default();
+                        let capacity = measure.get(&shelter.capacity);
+                        let mut measurements = Vec::new();
+                        let mut rejected = Vec::new();
+                        for observation in observations {
+                            let SignalObservation {
+                                observed_at,
+                                measurement,
+                            } = observation.clone();
+                            let (value, status) = match shelter
+                                .capacity_policy
+                                .apply(measurement.into(), capacity)
+                            {
+                                Ok(applied) => applied,
+                                Err(error) => {
+                                    let error = error
+                                        .downcast::<CapacityExceededError>()?;
+                                    rejected.push(RejectedSignalObservation {
+                                        observation,
+                                        reason: error.to_string(),
+                                    });
+                                    continue;
+                                }
+                            };
+                            let occupancy = match status {
+                                ShelterMeasurementStatus::Flagged => {
+                                    occupancy.clone()
+                                }
+                                _ => measure.replace(occupancy.clone(), value),
+                            };
+                            let Meta { id, updated_at, .. } = Meta::new();
+                            measurements.push(ShelterMeasurement {
+                                id,
+                                created_at: observed_at,
+                                updated_at,
+
+                                shelter_id,
+                                signal_id: Some(signal_id),
+                                user_id: None,
+
+                                capacity: shelter.capacity.clone(),
+                                occupancy,
+                                value: Some(value),
+                                delta: None,
+                                status,
+
+                                retracted_at: None,
+                                retracted_by_id: None,
+                                retraction_reason: None,
+                                correction_of_id: None,
+
+                                idempotency_key: None,
+                            });
+                        }
+                        if !measurements.is_empty() {
+                            let models = measurements
+                                .iter()
+                                .cloned()
+                                .map(ShelterMeasurementModel::try_from)
+                                .collect::<Result<Vec<_>>>()
+                                .context("failed to encode measurements")?;
+                            insert_into(measurements::table)
+                                .values(models)
+                                .execute(&conn)
+                                .context(
+                                    "failed to insert measurement models",
+                                )?;
+                        }
+
+                        // Recompute shelter occupancy, which only changes
+                        // if the observations are newer than the signal's
+                        // previous readings and any occupancy reported by
+                        // hand.
+                        let (occupancy, occupancy_updated_at) =
+                            recompute_shelter_occupancy(&conn, &shelter)?;
+                        let is_changed = occupancy != shelter.occupancy
+                            || occupancy_updated_at
+                                != shelter.occupancy_updated_at;
+                        if is_changed {
+                            let ShelterSpace { spots, beds } =
+                                occupancy.clone().unwrap_or_default();
+                            let updated_at = Utc::now();
+                            update(shelters::table.find(shelter_id))
+                                .set((
+                                    shelters::occupied_spots
+                                        .eq(i32::from(spots)),
+                                    shelters::occupied_beds.eq(i32::from(beds)),
+                                    shelters::occupancy_updated_at
+                                        .eq(occupancy_updated_at),
+                                    shelters::updated_at.eq(updated_at),
+                                ))
+                                .execute(&conn)
+                                .context("failed to update shelter model")?;
+                            shelter.occupancy = occupancy;
+                            shelter.occupancy_updated_at = occupancy_updated_at;
+                            shelter.updated_at = updated_at;
+                        }
+
+                        // Mark signal as seen.
+                        update(signals::table.find(signal_id))
+                            .set(signals::last_seen_at.eq(Utc::now()))
+                            .execute(&conn)
+                            .context("failed to update signal model")?;
+
+                        Ok((shelter, measurements, rejected, is_changed))
+                    })
                 },
-            }
+            )
+            .await
+            .unwrap()?
         };
-        dbg!(&occupancy);
 
-        // Mutate shelter occupancy.
-        shelter.occupancy = Some(occupancy.clone());
+        // Score measurements for anomalies.
+        self.internal_detect_measurement_anomalies(
+            &signal,
+            measurements.clone(),
+        );
+
+        // Notify occupancy watchers.
+        if let (true, Some(measurement)) = (is_changed, measurements.last()) {
+            self.occupancy_bus.publish(ShelterOccupancyEvent {
+                shelter: shelter.clone(),
+                measurement: measurement.clone(),
+            });
+        }
 
-        // Create measurement.
-        let measurement = {
-            let Meta {
-                id,
-                created_at,
-                updated_at,
-            } = Meta::new();
+        let response = CreateSignalMeasurementsResponse {
+            shelter,
+            measurements,
+            rejected,
+        };
+        Ok(response)
+    }
 
-            ShelterMeasurement {
-                id,
-                created_at,
-                updated_at,
+    pub async fn ping_signal(
+        &self,
+        context: &Context,
+        request: PingSignalRequest,
+    ) -> Result<PingSignalResponse> {
+        let PingSignalRequest { signal_id } = request;
+
+        // Assert signal can report measurements.
+        if !self
+            .can_report_signal_measurement(context, signal_id)
+            .await?
+        {
+            bail!("not authorized")
+        }
 
-                shelter_id: shelter.id,
-                signal_id,
+        // Assert signal is within its rate limit.
+        self.internal_throttle_signal(signal_id).await?;
 
-                capacity,
-                occupancy,
-            }
+        // Mark signal as seen, unless it is archived or disabled.
+        let signal = {
+            let pool = self.db_pool.clone();
+            let signal =
+                spawn_blocking(move || -> Result<Option<SignalModel>> {
+                    use schema::signals;
+                    let conn =
+                        pool.get().context("database connection failure")?;
+                    update(
+                        signals::table
+                            .find(signal_id)
+                            .filter(signals::archived_at.is_null())
+                            .filter(signals::disabled_at.is_null()),
+                    )
+                    .set(signals::last_seen_at.eq(Utc::now()))
+                    .get_result(&conn)
+                    .optional()
+                    .context("failed to update signal model")
+                })
+                .await
+                .unwrap()?
+                .context("signal is archived or disabled")?;
+            Signal::try_from(signal).context("failed to decode signal")?
         };
 
-        // Update shelter and measurement in database.
-        {
+        let response = PingSignalResponse { signal };
+        Ok(response)
+    }
+
+    pub async fn rotate_signal_secret(
+        &self,
+        context: &Context,
+        request: RotateSignalSecretRequest,
+    ) -> Result<RotateSignalSecretResponse> {
+        let RotateSignalSecretRequest {
+            signal_id,
+            grace_period,
+        } = request;
+
+        // Assert signal is editable.
+        if !self.can_edit_signal(context, signal_id).await? {
+            bail!("not authorized")
+        }
+
+        // Validate grace period.
+        let grace_period = grace_period
+            .map(ChronoDuration::from_std)
+            .transpose()
+            .context("invalid grace period")?;
+        if let Some(grace_period) = grace_period {
+            if grace_period > *MAX_SECRET_GRACE_PERIOD {
+                bail!("grace period too long");
+            }
+        }
+
+        // Replace secret, keeping the current one valid during the grace
+        // period, and re-enable the signal, in case it was disabled for
+        // exceeding its rate limit.
+        //
+        // Only these columns are updated, so that concurrent changes to the
+        // rest of the signal aren't overwritten.
+        let secret = generate_secret();
+        let signal = {
             let pool = self.db_pool.clone();
-            let shelter_id = signal.shelter_id;
-            let shelter = ShelterModel::try_from(shelter.clone())
-                .context("failed to encode shelter")?;
-            let measurement =
-                ShelterMeasurementModel::try_from(measurement.clone())
-                    .context("failed to encode measurement")?;
-            spawn_blocking(move || -> Result<()> {
-                use schema::shelter_measurements as measurements;
-                use schema::shelters;
+            let secret_hash = SecretHash::new(&secret).to_string();
+            let signal = spawn_blocking(move || -> Result<SignalModel> {
+                use schema::signals;
                 let conn = pool.get().context("database connection failure")?;
                 conn.transaction(|| {
-                    update(shelters::table.find(shelter_id))
-                        .set(shelter)
-                        .execute(&conn)
-                        .context("failed to insert shelter model")?;
-                    insert_into(measurements::table)
-                        .values(measurement)
-                        .execute(&conn)
-                        .context("failed to insert measurement model")?;
-                    Ok(())
+                    let (current_hash, archived_at): (
+                        String,
+                        Option<DateTime>,
+                    ) = signals::table
+                        .find(signal_id)
+                        .select((signals::secret_hash, signals::archived_at))
+                        .for_update()
+                        .first(&conn)
+                        .optional()
+                        .context("failed to load signal model")?
+                        .context("signal not found")?;
+                    if archived_at.is_some() {
+                        bail!("signal is archived")
+                    }
+
+                    let now = Utc::now();
+                    let (previous_hash, previous_expires_at) =
+                        match grace_period {
+                            Some(grace_period) => {
+                                (Some(current_hash), Some(now + grace_period))
+                            }
+                            None => (None, None),
+                        };
+                    update(signals::table.find(signal_id))
+                        .set((
+                            signals::secret_hash.eq(secret_hash),
+                            signals::previous_secret_hash.eq(previous_hash),
+                            signals::previous_secret_expires_at
+                                .eq(previous_expires_at),
+                            signals::disabled_at.eq(None::<DateTime>),
+                            signals::updated_at.eq(now),
+                        ))
+                        .get_result(&conn)
+                        .context("failed to update signal model")
                 })
             })
             .await
-            .unwrap()?
+            .unwrap()?;
+            Signal::try_from(signal).context("failed to decode signal model")?
         };
 
-        let response = CreateSignalMeasurementResponse {
-            shelter,
-            measurement,
-        };
+        // Forget the signal's past rate limit violations.
+        self.signal_violation_limiter.reset(&signal_id);
+
+        let response = RotateSignalSecretResponse { signal, secret };
         Ok(response)
     }
 
@@ -718,13 +1872,6 @@ impl Service {
             .unwrap()?
         };
 
-        // If signal has created measurements, then it can't be deleted.
-        //
-        // TODO: Soft delete used signals.
-        if measurements > 0 {
-            bail!("used signals cannot be deleted")
-        }
-
         // Get associated shelter.
         let shelter = {
             let pool = self.db_pool.clone();
@@ -742,16 +1889,73 @@ impl Service {
             Shelter::try_from(shelter).context("failed to decode shelter")?
         };
 
-        // Delete signal.
-        {
+        // Delete signal, or archive it if it has created measurements, so
+        // that they can still refer to it. Archiving a signal removes its
+        // readings from the shelter's occupancy.
+        let shelter = {
             let pool = self.db_pool.clone();
-            spawn_blocking(move || -> Result<()> {
-                use schema::signals;
+            spawn_blocking(move || -> Result<Shelter> {
+                use schema::{shelters, signals};
                 let conn = pool.get().context("database connection failure")?;
-                delete_from(signals::table.find(signal_id))
-                    .execute(&conn)
-                    .context("failed to delete signal model")?;
-                Ok(())
+                if measurements == 0 {
+                    delete_from(signals::table.find(signal_id))
+                        .execute(&conn)
+                        .context("failed to delete signal model")?;
+                    return Ok(shelter);
+                }
+                conn.transaction(|| {
+                    let shelter: ShelterModel = shelters::table
+                        .find(shelter.id)
+                        .select(SHELTER_COLUMNS)
+                        .for_update()
+                        .first(&conn)
+                        .context("failed to load shelter model")?;
+                    let mut shelter = Shelter::try_from(shelter)
+                        .context("failed to decode shelter model")?;
+
+                    // Archive signal.
+                    let now = Utc::now();
+                    let measure: Option<String> = update(
+                        signals::table
+                            .find(signal_id)
+                            .filter(signals::archived_at.is_null()),
+                    )
+                    .set((
+                        signals::archived_at.eq(now),
+                        signals::updated_at.eq(now),
+                    ))
+                    .returning(signals::measure)
+                    .get_result(&conn)
+                    .optional()
+                    .context("failed to archive signal model")?;
+                    let measure: ShelterMeasure = match measure {
+                        Some(measure) => {
+                            measure.parse().context("invalid signal measure")?
+                        }
+                        None => return Ok(shelter),
+                    };
+
+                    // Re-aggregate shelter occupancy.
+                    let occupancy =
+                        shelter.occupancy.clone().unwrap_or_default();
+                    if let Some(aggregate) =
+                        aggregate_shelter_occupancy(&conn, &shelter, &measure)?
+                    {
+                        let occupancy = measure.replace(occupancy, aggregate);
+                        let ShelterSpace { spots, beds } = &occupancy;
+                        update(shelters::table.find(shelter.id))
+                            .set((
+                                shelters::occupied_spots.eq(i32::from(*spots)),
+                                shelters::occupied_beds.eq(i32::from(*beds)),
+                                shelters::updated_at.eq(now),
+                            ))
+                            .execute(&conn)
+                            .context("failed to update shelter model")?;
+                        shelter.occupancy = Some(occupancy);
+                        shelter.updated_at = now;
+                    }
+                    Ok(shelter)
+                })
             })
             .await
             .unwrap()?
@@ -67,8 +67,23 @@ impl FromStr for Slug {
     }
 }
 
+/// Generate a random tail, which must start with an alphanumeric character
+/// in order to be a valid slug on its own.
 fn generate_tail() -> String {
     let bytes: [u8; 12] = random();
     let tail = encode_base64(bytes, URL_SAFE_NO_PAD);
-    tail.replace('_', "-")
+    tail.trim_start_matches(&['-', '_'][..]).replace('_', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_slugs_are_valid() {
+        for _ in 0..1000 {
+            let slug = Slug::default();
+            assert!(slug.as_str().parse::<Slug>().is_ok(), "{}", slug);
+        }
+    }
 }
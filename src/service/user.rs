@@ -96,7 +96,7 @@ impl Service {
         context: &Context,
         user_id: Uuid,
     ) -> Result<bool> {
-        if context.is_internal() {
+        if context.is_privileged() {
             return Ok(true);
         }
 
@@ -105,6 +105,11 @@ impl Service {
             return Ok(true);
         }
 
+        // Members of a shelter can view each other.
+        if let Some(viewer) = context.viewing_user() {
+            return self.internal_share_shelter(viewer.id, user_id).await;
+        }
+
         Ok(false)
     }
 
@@ -113,7 +118,7 @@ impl Service {
         context: &Context,
         user_id: Uuid,
     ) -> Result<bool> {
-        if context.is_internal() {
+        if context.is_privileged() {
             return Ok(true);
         }
 
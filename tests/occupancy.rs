@@ -0,0 +1,384 @@
+//! Tests for shelter occupancy updates, including concurrent ones.
+//!
+//! These tests need a migrated database at `API_DATABASE_URL` (which may be
+//! set in `.env`), so they are ignored by default. Run them with:
+//!
+//! ```bash
+//! cargo test --test occupancy -- --ignored
+//! ```
+
+use anyhow::{bail, Context as ResultContext, Result};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel::{delete, r2d2::ConnectionManager};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+use api::db::PgPool;
+use api::env::{load as load_env, var as env_var};
+use api::schema::{shelter_measurements, shelters, signals, users};
+use api::service::CreateSignalRequest;
+use api::service::GetUserRequest;
+use api::service::{Address, Coordinate, Service, ShelterFood, ShelterSpace};
+use api::service::{Context, Principal};
+use api::service::{
+    CorrectShelterMeasurementRequest, ListShelterMeasurementsRequest,
+};
+use api::service::{CountShelterMeasurementsRequest, CreateShelterRequest};
+use api::service::{CreateSignalMeasurementRequest, GetShelterRequest};
+use api::service::{CreateSignalMeasurementsRequest, SignalObservation};
+use api::service::{CreateUserRequest, UpdateShelterOccupancyRequest};
+use api::service::{RateLimit, RateLimiter};
+use api::service::{
+    RetractShelterMeasurementRequest, ShelterMeasurementCorrection,
+};
+use api::service::{ShelterMeasure, SignalMeasurement};
+
+/// The number of measurements each signal reports at once.
+const MEASUREMENTS: u16 = 200;
+
+#[test]
+#[ignore]
+fn concurrent_measurements_are_not_lost() -> Result<()> {
+    load_env().context("failed to load environment variables")?;
+    let url = env_var("DATABASE_URL").context("missing database URL")?;
+    let pool = PgPool::builder()
+        .max_size(16)
+        .build(ConnectionManager::new(url))
+        .context("failed to connect to database")?;
+    let service = Service::builder()
+        .db_pool(pool.clone())
+        .signal_rate_limiter(RateLimiter::new(RateLimit::per_minute(
+            MEASUREMENTS.into(),
+        )))
+        .build()?;
+    let service = Arc::new(service);
+
+    let runtime = Runtime::new().context("failed to initialize runtime")?;
+    let shelter_id =
+        runtime.block_on(create_shelter(&service, "+1 519 555 0100"))?;
+    let result = runtime.block_on(report_measurements(&service, shelter_id));
+
+    // Clean up before checking the result.
+    let conn = pool.get().context("database connection failure")?;
+    delete_shelter(&conn, shelter_id)?;
+
+    let (occupancy, count) = result?;
+    assert_eq!(occupancy.beds, MEASUREMENTS, "beds deltas were lost");
+    assert_eq!(count, 2 * i64::from(MEASUREMENTS), "measurements were lost");
+    Ok(())
+}
+
+#[test]
+#[ignore]
+fn old_observations_do_not_replace_newer_reports() -> Result<()> {
+    load_env().context("failed to load environment variables")?;
+    let url = env_var("DATABASE_URL").context("missing database URL")?;
+    let pool = PgPool::builder()
+        .build(ConnectionManager::new(url))
+        .context("failed to connect to database")?;
+    let service = Service::builder().db_pool(pool.clone()).build()?;
+
+    let runtime = Runtime::new().context("failed to initialize runtime")?;
+    let shelter_id =
+        runtime.block_on(create_shelter(&service, "+1 519 555 0101"))?;
+    let user_id = runtime.block_on(create_admin(&service))?;
+    let result = runtime.block_on(report_observations_after_hand(
+        &service, shelter_id, user_id,
+    ));
+
+    // Clean up before checking the result.
+    let conn = pool.get().context("database connection failure")?;
+    delete_shelter(&conn, shelter_id)?;
+    delete(users::table.find(user_id)).execute(&conn)?;
+
+    let (before, after) = result?;
+    assert_eq!(before, 10, "old observations replaced a newer report");
+    assert_eq!(after, 8, "new observations were ignored");
+    Ok(())
+}
+
+/// Report an occupancy by hand, followed by observations made before it and
+/// then after it, returning the occupied spots after each batch.
+async fn report_observations_after_hand(
+    service: &Service,
+    shelter_id: Uuid,
+    user_id: Uuid,
+) -> Result<(u16, u16)> {
+    let signal_id =
+        create_signal(service, shelter_id, ShelterMeasure::Spots).await?;
+    let reported_at = {
+        let user = {
+            let context = Context::system();
+            let request = GetUserRequest { user_id };
+            let response = service.get_user(&context, request).await?;
+            response.user.context("user not found")?
+        };
+        let context = Context::new(Principal::user(user));
+        let request = UpdateShelterOccupancyRequest {
+            shelter_id,
+            occupancy: ShelterSpace { spots: 10, beds: 0 },
+        };
+        let response =
+            service.update_shelter_occupancy(&context, request).await?;
+        response.measurement.created_at
+    };
+
+    let context = Context::new(Principal::Signal(signal_id));
+    let mut spots = Vec::new();
+    for (observed_at, measurement) in
+        &[(reported_at - Duration::minutes(5), 7), (Utc::now(), 8)]
+    {
+        let request = CreateSignalMeasurementsRequest {
+            signal_id,
+            observations: vec![SignalObservation {
+                observed_at: *observed_at,
+                measurement: *measurement,
+            }],
+        };
+        let response = service
+            .create_signal_measurements(&context, request)
+            .await?;
+        let occupancy =
+            response.shelter.occupancy.context("missing occupancy")?;
+        spots.push(occupancy.spots);
+    }
+    Ok((spots[0], spots[1]))
+}
+
+#[test]
+#[ignore]
+fn later_deltas_are_replayed_after_corrections() -> Result<()> {
+    load_env().context("failed to load environment variables")?;
+    let url = env_var("DATABASE_URL").context("missing database URL")?;
+    let pool = PgPool::builder()
+        .build(ConnectionManager::new(url))
+        .context("failed to connect to database")?;
+    let service = Service::builder().db_pool(pool.clone()).build()?;
+
+    let runtime = Runtime::new().context("failed to initialize runtime")?;
+    let shelter_id =
+        runtime.block_on(create_shelter(&service, "+1 519 555 0102"))?;
+    let result = runtime.block_on(correct_then_retract(&service, shelter_id));
+
+    // Clean up before checking the result.
+    let conn = pool.get().context("database connection failure")?;
+    delete_shelter(&conn, shelter_id)?;
+
+    let (corrected, retracted) = result?;
+    assert_eq!(corrected, vec![7, 6, 4], "deltas weren't replayed");
+    assert_eq!(retracted, vec![3, 2], "deltas weren't replayed");
+    Ok(())
+}
+
+/// Report a reading followed by two deltas, then correct the reading and
+/// retract the correction, returning the readings after each change (newest
+/// first), with the shelter's spots first.
+async fn correct_then_retract(
+    service: &Service,
+    shelter_id: Uuid,
+) -> Result<(Vec<u16>, Vec<u16>)> {
+    let signal_id =
+        create_signal(service, shelter_id, ShelterMeasure::Spots).await?;
+    let mut measurement_id = None;
+    for measurement in &[
+        SignalMeasurement::Absolute(5),
+        SignalMeasurement::Delta(2),
+        SignalMeasurement::Delta(1),
+    ] {
+        let context = Context::new(Principal::Signal(signal_id));
+        let request = CreateSignalMeasurementRequest {
+            signal_id,
+            measurement: *measurement,
+            idempotency_key: None,
+        };
+        let response =
+            service.create_signal_measurement(&context, request).await?;
+        measurement_id.get_or_insert(response.measurement.id);
+    }
+    let measurement_id = measurement_id.context("missing measurement")?;
+
+    let context = Context::system();
+    let request = CorrectShelterMeasurementRequest {
+        measurement_id,
+        reason: "Miscounted".parse()?,
+        correction: ShelterMeasurementCorrection::Value(4),
+    };
+    let response = service
+        .correct_shelter_measurement(&context, request)
+        .await?;
+    let correction_id = response.measurement.id;
+    let corrected = list_readings(service, shelter_id).await?;
+
+    let request = RetractShelterMeasurementRequest {
+        measurement_id: correction_id,
+        reason: "Not a real reading".parse()?,
+    };
+    service
+        .retract_shelter_measurement(&context, request)
+        .await?;
+    let retracted = list_readings(service, shelter_id).await?;
+    Ok((corrected, retracted))
+}
+
+/// List the readings of a shelter's measurements, newest first, after
+/// checking that the latest one matches the shelter's occupied spots.
+async fn list_readings(
+    service: &Service,
+    shelter_id: Uuid,
+) -> Result<Vec<u16>> {
+    let context = Context::system();
+    let spots = {
+        let request = GetShelterRequest { shelter_id };
+        let response = service.get_shelter(&context, request).await?;
+        let shelter = response.shelter.context("shelter not found")?;
+        shelter.occupancy.context("missing occupancy")?.spots
+    };
+    let request = ListShelterMeasurementsRequest {
+        shelter_id,
+        filter: Default::default(),
+        after: None,
+        before: None,
+        limit: 10,
+        from_end: false,
+    };
+    let response = service.list_shelter_measurements(&context, request).await?;
+    let readings = response
+        .measurements
+        .into_iter()
+        .map(|measurement| measurement.value.context("missing reading"))
+        .collect::<Result<Vec<_>>>()?;
+    if readings.first() != Some(&spots) {
+        bail!("occupancy of {} doesn't match readings", spots)
+    }
+    Ok(readings)
+}
+
+async fn create_shelter(service: &Service, phone: &str) -> Result<Uuid> {
+    let context = Context::system();
+    let request = CreateShelterRequest {
+        name: format!("Stress Test {}", Uuid::new_v4()).parse()?,
+        about: None,
+        image_url: None,
+        email: None,
+        phone: phone.parse()?,
+        website_url: None,
+        address: Address {
+            line1: "1 King St".to_owned(),
+            line2: None,
+            city: "Kitchener".to_owned(),
+            region: "Ontario".to_owned(),
+            country: "Canada".to_owned(),
+            postcode: "N2G 1A1".to_owned(),
+        },
+        location: Coordinate {
+            x: -80.49,
+            y: 43.45,
+        },
+        capacity: ShelterSpace {
+            spots: MEASUREMENTS,
+            beds: MEASUREMENTS,
+        },
+        food: ShelterFood::None,
+        tags: Default::default(),
+    };
+    let response = service.create_shelter(&context, request).await?;
+    Ok(response.shelter.id)
+}
+
+/// Report absolute spots measurements and delta beds measurements at the
+/// same time, which each update a different column of the same shelter.
+async fn report_measurements(
+    service: &Arc<Service>,
+    shelter_id: Uuid,
+) -> Result<(ShelterSpace, i64)> {
+    let spots_signal_id =
+        create_signal(service, shelter_id, ShelterMeasure::Spots).await?;
+    let beds_signal_id =
+        create_signal(service, shelter_id, ShelterMeasure::Beds).await?;
+
+    let mut handles = Vec::new();
+    for value in 1..=MEASUREMENTS {
+        let reports = [
+            (spots_signal_id, SignalMeasurement::Absolute(value)),
+            (beds_signal_id, SignalMeasurement::Delta(1)),
+        ];
+        for (signal_id, measurement) in reports.iter().copied() {
+            let service = service.clone();
+            let handle = tokio::spawn(async move {
+                let context = Context::new(Principal::Signal(signal_id));
+                let request = CreateSignalMeasurementRequest {
+                    signal_id,
+                    measurement,
+                    idempotency_key: None,
+                };
+                service.create_signal_measurement(&context, request).await
+            });
+            handles.push(handle);
+        }
+    }
+    for handle in handles {
+        handle.await?.context("failed to create measurement")?;
+    }
+
+    let context = Context::system();
+    let request = GetShelterRequest { shelter_id };
+    let response = service.get_shelter(&context, request).await?;
+    let shelter = response.shelter.context("shelter not found")?;
+    let occupancy = shelter.occupancy.context("missing occupancy")?;
+
+    let count = {
+        let request = CountShelterMeasurementsRequest {
+            shelter_id,
+            filter: Default::default(),
+        };
+        let response = service
+            .count_shelter_measurements(&context, request)
+            .await?;
+        i64::from(response.count)
+    };
+    Ok((occupancy, count))
+}
+
+async fn create_signal(
+    service: &Service,
+    shelter_id: Uuid,
+    measure: ShelterMeasure,
+) -> Result<Uuid> {
+    let context = Context::system();
+    let request = CreateSignalRequest {
+        name: format!("{} Counter", measure).parse()?,
+        shelter_id,
+        measure,
+    };
+    let response = service.create_signal(&context, request).await?;
+    Ok(response.signal.id)
+}
+
+async fn create_admin(service: &Service) -> Result<Uuid> {
+    let context = Context::system();
+    let request = CreateUserRequest {
+        first_name: "Test".parse()?,
+        last_name: "Admin".parse()?,
+        about: None,
+        image_url: None,
+        email: None,
+        phone: None,
+        firebase_id: format!("test-{}", Uuid::new_v4()),
+        is_admin: true,
+    };
+    let response = service.create_user(&context, request).await?;
+    Ok(response.user.id)
+}
+
+fn delete_shelter(conn: &PgConnection, shelter_id: Uuid) -> Result<()> {
+    delete(shelter_measurements::table)
+        .filter(shelter_measurements::shelter_id.eq(shelter_id))
+        .execute(conn)?;
+    delete(signals::table)
+        .filter(signals::shelter_id.eq(shelter_id))
+        .execute(conn)?;
+    delete(shelters::table.find(shelter_id)).execute(conn)?;
+    Ok(())
+}